@@ -0,0 +1,9 @@
+-- Per-answer details so we can see which questions split people
+-- Timeouts are stored with a NULL choice, and questions from the built-in deck
+-- or a pack (which have no row in `questions`) with a NULL question_id
+
+ALTER TABLE responses
+    MODIFY question_id INT NULL,
+    MODIFY choice ENUM('A', 'B') NULL,
+    ADD COLUMN question_text VARCHAR(255) NULL AFTER question_id,
+    ADD COLUMN reaction_ms INT NULL AFTER choice;
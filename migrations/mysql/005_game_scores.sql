@@ -0,0 +1,11 @@
+-- Aggregate result per finished session
+-- IF NOT EXISTS: older builds of the game created this table themselves
+
+CREATE TABLE IF NOT EXISTS game_scores (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    session_id VARCHAR(36) NOT NULL,
+    score_left INT NOT NULL,
+    score_right INT NOT NULL,
+    result_type VARCHAR(50) NOT NULL,
+    played_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
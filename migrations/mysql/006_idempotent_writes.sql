@@ -0,0 +1,12 @@
+-- Writes can be replayed from the offline queue, so a repeated write must not add a row
+-- Responses are numbered per session; game_scores gets one row per session
+
+ALTER TABLE responses
+    ADD COLUMN seq INT NULL AFTER session_id,
+    ADD UNIQUE KEY unique_session_seq (session_id, seq);
+
+-- Keep the first score of any session that was saved twice before the key existed
+DELETE newer FROM game_scores newer
+    JOIN game_scores older ON newer.session_id = older.session_id AND newer.id > older.id;
+
+ALTER TABLE game_scores ADD UNIQUE KEY unique_session (session_id);
@@ -0,0 +1,8 @@
+-- Couples mode: both partners' answers go in `responses`, told apart by `player`
+-- Each finished session records its mode, and couples sessions their compatibility
+
+ALTER TABLE responses ADD COLUMN player VARCHAR(50) NULL AFTER session_id;
+
+ALTER TABLE game_scores
+    ADD COLUMN mode VARCHAR(20) NOT NULL DEFAULT 'solo' AFTER session_id,
+    ADD COLUMN compatibility INT NULL AFTER result_type;
@@ -0,0 +1,6 @@
+-- Timeout policy: answers the game picked when the timer ran out are flagged,
+-- and each finished session counts how many it had
+
+ALTER TABLE responses ADD COLUMN auto_picked BOOLEAN NOT NULL DEFAULT FALSE AFTER choice;
+
+ALTER TABLE game_scores ADD COLUMN auto_picks INT NOT NULL DEFAULT 0 AFTER score_right;
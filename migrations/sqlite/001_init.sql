@@ -0,0 +1,39 @@
+-- Two People score schema for SQLite
+-- Applied automatically on startup when DATABASE_URL starts with sqlite:
+-- There is no questions table here: SQLite players use the built-in deck or a pack
+
+CREATE TABLE IF NOT EXISTS player_sessions (
+    id TEXT PRIMARY KEY,
+    started_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP NULL
+);
+
+-- Timeouts have a NULL choice, as in the MySQL schema
+CREATE TABLE IF NOT EXISTS responses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id TEXT NOT NULL REFERENCES player_sessions(id) ON DELETE CASCADE,
+    question_id INTEGER NULL,
+    question_text TEXT NULL,
+    choice TEXT NULL CHECK (choice IN ('A', 'B')),
+    reaction_ms INTEGER NULL,
+    answered_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS personality_scores (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id TEXT NOT NULL REFERENCES player_sessions(id) ON DELETE CASCADE,
+    trait_name TEXT NOT NULL,
+    trait_value TEXT NOT NULL,
+    score INTEGER DEFAULT 0,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (session_id, trait_name)
+);
+
+CREATE TABLE IF NOT EXISTS game_scores (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id TEXT NOT NULL,
+    score_left INTEGER NOT NULL,
+    score_right INTEGER NOT NULL,
+    result_type TEXT NOT NULL,
+    played_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,7 @@
+-- Writes can be replayed from the offline queue, so a repeated write must not add a row
+
+ALTER TABLE responses ADD COLUMN seq INTEGER NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS unique_session_seq ON responses (session_id, seq);
+
+DELETE FROM game_scores WHERE id NOT IN (SELECT MIN(id) FROM game_scores GROUP BY session_id);
+CREATE UNIQUE INDEX IF NOT EXISTS unique_session ON game_scores (session_id);
@@ -0,0 +1,6 @@
+-- Couples mode: both partners' answers go in `responses`, told apart by `player`
+-- Each finished session records its mode, and couples sessions their compatibility
+
+ALTER TABLE responses ADD COLUMN player TEXT NULL;
+ALTER TABLE game_scores ADD COLUMN mode TEXT NOT NULL DEFAULT 'solo';
+ALTER TABLE game_scores ADD COLUMN compatibility INTEGER NULL;
@@ -0,0 +1,5 @@
+-- Timeout policy: answers the game picked when the timer ran out are flagged,
+-- and each finished session counts how many it had
+
+ALTER TABLE responses ADD COLUMN auto_picked INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE game_scores ADD COLUMN auto_picks INTEGER NOT NULL DEFAULT 0;
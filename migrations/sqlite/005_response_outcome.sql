@@ -0,0 +1,7 @@
+-- Whether each response was answered, timed out or skipped. Earlier rows with no choice
+-- or an auto-pick are counted as timeouts, skips included, since nothing told them apart.
+
+ALTER TABLE responses
+    ADD COLUMN outcome TEXT NOT NULL DEFAULT 'answered' CHECK (outcome IN ('answered', 'timed_out', 'skipped'));
+
+UPDATE responses SET outcome = 'timed_out' WHERE choice IS NULL OR auto_picked;
@@ -0,0 +1,484 @@
+//! Input actions. Gameplay reads `Actions` rather than raw devices; each frame they're filled
+//! from whatever keys, mouse buttons and gamepad buttons `Bindings` maps to each action.
+//! Bindings are changed on the controls screen and saved to `two-people/bindings.ron` in the
+//! user's config directory.
+
+use crate::Choice;
+use bevy::input::{gamepad::Gamepad, ButtonInput};
+use bevy::log::{info, warn};
+use bevy::prelude::{GamepadButton, KeyCode, MouseButton, Resource};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    ChooseLeft,
+    ChooseRight,
+    /// Couples mode splits the keyboard, so each partner has their own pair
+    P1Left,
+    P1Right,
+    P2Left,
+    P2Right,
+    /// Party mode: the next player is ready to take over
+    Confirm,
+    Restart,
+    Pause,
+    /// From the results screen
+    ChangeCategories,
+    MainMenu,
+    Mute,
+    Skip,
+}
+
+/// Where an action gets read. Two actions can share a binding as long as they're never read
+/// in the same place.
+#[derive(Clone, Copy, PartialEq)]
+enum Context {
+    /// A question on screen in any mode but couples
+    Solo,
+    Couples,
+    Handover,
+    Paused,
+    Results,
+}
+
+impl Action {
+    /// In the order the controls screen lists them
+    pub const ALL: [Action; 13] = [
+        Action::ChooseLeft,
+        Action::ChooseRight,
+        Action::P1Left,
+        Action::P1Right,
+        Action::P2Left,
+        Action::P2Right,
+        Action::Confirm,
+        Action::Restart,
+        Action::Pause,
+        Action::ChangeCategories,
+        Action::MainMenu,
+        Action::Mute,
+        Action::Skip,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::ChooseLeft => "CHOOSE LEFT",
+            Action::ChooseRight => "CHOOSE RIGHT",
+            Action::P1Left => "P1 LEFT",
+            Action::P1Right => "P1 RIGHT",
+            Action::P2Left => "P2 LEFT",
+            Action::P2Right => "P2 RIGHT",
+            Action::Confirm => "CONFIRM",
+            Action::Restart => "RESTART",
+            Action::Pause => "PAUSE",
+            Action::ChangeCategories => "CATEGORIES",
+            Action::MainMenu => "MAIN MENU",
+            Action::Mute => "MUTE",
+            Action::Skip => "SKIP",
+        }
+    }
+
+    /// The action that picks `choice`
+    pub fn choose(choice: Choice) -> Self {
+        match choice {
+            Choice::Left => Action::ChooseLeft,
+            Choice::Right => Action::ChooseRight,
+        }
+    }
+
+    fn choice(self) -> Option<Choice> {
+        match self {
+            Action::ChooseLeft => Some(Choice::Left),
+            Action::ChooseRight => Some(Choice::Right),
+            _ => None,
+        }
+    }
+
+    /// The action that picks `choice` for couples player `player` (0 or 1)
+    pub fn player(player: usize, choice: Choice) -> Self {
+        match (player, choice) {
+            (0, Choice::Left) => Action::P1Left,
+            (0, Choice::Right) => Action::P1Right,
+            (_, Choice::Left) => Action::P2Left,
+            (_, Choice::Right) => Action::P2Right,
+        }
+    }
+
+    /// The couples player this action picks for, and what it picks
+    fn player_choice(self) -> Option<(usize, Choice)> {
+        match self {
+            Action::P1Left => Some((0, Choice::Left)),
+            Action::P1Right => Some((0, Choice::Right)),
+            Action::P2Left => Some((1, Choice::Left)),
+            Action::P2Right => Some((1, Choice::Right)),
+            _ => None,
+        }
+    }
+
+    /// Where `binding` triggers this action. Couples reads the choose actions from gamepads
+    /// only, since the keyboard goes to the per-player actions there.
+    fn contexts(self, binding: Binding) -> &'static [Context] {
+        use Context::*;
+        match self {
+            Action::ChooseLeft | Action::ChooseRight if matches!(binding, Binding::Pad(_)) => &[Solo, Couples],
+            Action::ChooseLeft | Action::ChooseRight | Action::Skip => &[Solo],
+            Action::P1Left | Action::P1Right | Action::P2Left | Action::P2Right => &[Couples],
+            Action::Confirm => &[Handover],
+            // START pauses everywhere else, so it only stands in for R on the results screen
+            Action::Restart if binding == Binding::Pad(GamepadButton::Start) => &[Results],
+            Action::Restart => &[Paused, Results],
+            Action::Pause => &[Solo, Couples, Handover, Paused],
+            Action::ChangeCategories | Action::MainMenu => &[Results],
+            Action::Mute => &[Solo, Couples, Handover, Paused, Results],
+        }
+    }
+
+    /// Whether `binding` can't trigger both this and `other` without one of them misfiring
+    fn clashes(self, other: Action, binding: Binding) -> bool {
+        // A mouse button on a choose action only fires over that action's card, so clicking
+        // can pick either one
+        let pointed = matches!(binding, Binding::Mouse(_)) && self.choice().is_some() && other.choice().is_some();
+        self != other && !pointed && self.contexts(binding).iter().any(|c| other.contexts(binding).contains(c))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Pad(GamepadButton),
+}
+
+impl Binding {
+    /// Name for the controls screen, like `A`, `ARROW LEFT` or `PAD START`
+    pub fn label(self) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{:?}", key);
+                spaced(name.strip_prefix("Key").or(name.strip_prefix("Digit")).unwrap_or(&name))
+            }
+            Binding::Mouse(button) => format!("MOUSE {}", spaced(&format!("{:?}", button))),
+            Binding::Pad(button) => format!("PAD {}", spaced(&format!("{:?}", button))),
+        }
+    }
+}
+
+/// `ArrowLeft` -> `ARROW LEFT`
+fn spaced(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_uppercase() && prev_lower {
+            out.push(' ');
+        }
+        prev_lower = c.is_lowercase();
+        out.extend(c.to_uppercase());
+    }
+    out
+}
+
+/// What each action is bound to
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct Bindings(HashMap<Action, Vec<Binding>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Binding::{Key, Mouse, Pad};
+        Self(HashMap::from([
+            (
+                Action::ChooseLeft,
+                vec![
+                    Key(KeyCode::ArrowLeft),
+                    Key(KeyCode::KeyA),
+                    Key(KeyCode::Digit1),
+                    Mouse(MouseButton::Left),
+                    Pad(GamepadButton::DPadLeft),
+                    Pad(GamepadButton::LeftTrigger),
+                    Pad(GamepadButton::West),
+                ],
+            ),
+            (
+                Action::ChooseRight,
+                vec![
+                    Key(KeyCode::ArrowRight),
+                    Key(KeyCode::KeyD),
+                    Key(KeyCode::Digit2),
+                    Mouse(MouseButton::Left),
+                    Pad(GamepadButton::DPadRight),
+                    Pad(GamepadButton::RightTrigger),
+                    Pad(GamepadButton::East),
+                ],
+            ),
+            (Action::P1Left, vec![Key(KeyCode::KeyA)]),
+            (Action::P1Right, vec![Key(KeyCode::KeyD)]),
+            (Action::P2Left, vec![Key(KeyCode::ArrowLeft)]),
+            (Action::P2Right, vec![Key(KeyCode::ArrowRight)]),
+            (
+                Action::Confirm,
+                vec![
+                    Key(KeyCode::Enter),
+                    Key(KeyCode::Space),
+                    Mouse(MouseButton::Left),
+                    Pad(GamepadButton::South),
+                ],
+            ),
+            (
+                Action::Restart,
+                vec![Key(KeyCode::KeyR), Pad(GamepadButton::Select), Pad(GamepadButton::Start)],
+            ),
+            (Action::Pause, vec![Key(KeyCode::Escape), Pad(GamepadButton::Start)]),
+            (Action::ChangeCategories, vec![Key(KeyCode::KeyC)]),
+            (Action::MainMenu, vec![Key(KeyCode::KeyM)]),
+            (Action::Mute, vec![Key(KeyCode::KeyV)]),
+            (Action::Skip, vec![Key(KeyCode::KeyS), Pad(GamepadButton::North)]),
+        ]))
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Binds `binding` to `action`, taking it off any action it would clash with. Returns
+    /// the actions it was taken from.
+    pub fn add(&mut self, action: Action, binding: Binding) -> Vec<Action> {
+        let mut taken = Vec::new();
+        for (&other, bound) in self.0.iter_mut() {
+            if action.clashes(other, binding) && bound.contains(&binding) {
+                bound.retain(|&b| b != binding);
+                taken.push(other);
+            }
+        }
+        let bound = self.0.entry(action).or_default();
+        if !bound.contains(&binding) {
+            bound.push(binding);
+        }
+        taken
+    }
+
+    /// Drops bindings that clash with one earlier in `Action::ALL`, as an older or hand-edited
+    /// file can have
+    fn resolve_clashes(&mut self) {
+        for (i, &first) in Action::ALL.iter().enumerate() {
+            let kept = self.get(first).to_vec();
+            for &later in &Action::ALL[i + 1..] {
+                let Some(bound) = self.0.get_mut(&later) else {
+                    continue;
+                };
+                bound.retain(|&b| {
+                    let clash = kept.contains(&b) && first.clashes(later, b);
+                    if clash {
+                        warn!("{} was bound to {} and {}, keeping the first", b.label(), first.label(), later.label());
+                    }
+                    !clash
+                });
+            }
+        }
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.0.insert(action, Vec::new());
+    }
+
+    /// Saved bindings, with the defaults for any action the file doesn't mention
+    pub fn load() -> Self {
+        let mut bindings = Self::default();
+        let Some(path) = bindings_path() else {
+            return bindings;
+        };
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return bindings;
+        };
+        match ron::from_str::<Bindings>(&text) {
+            Ok(saved) => {
+                info!("Loaded key bindings from {}", path.display());
+                bindings.0.extend(saved.0);
+                bindings.resolve_clashes();
+            }
+            Err(e) => warn!("Ignoring {} ({}), using the default bindings", path.display(), e),
+        }
+        bindings
+    }
+
+    pub fn save(&self) {
+        let Some(path) = bindings_path() else {
+            warn!("No config directory, key bindings won't be kept");
+            return;
+        };
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                std::fs::write(&path, text).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            warn!("Couldn't save key bindings to {}: {}", path.display(), e);
+        }
+    }
+
+    /// What the keyboard and mouse are doing this frame. `pointed` is the card under the
+    /// cursor: mouse buttons bound to a choose action only count over that action's card.
+    pub fn read_local(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+        pointed: Option<Choice>,
+    ) -> ActionSet {
+        self.read(|action, binding, just| match binding {
+            Binding::Key(key) if just => keyboard.just_pressed(key),
+            Binding::Key(key) => keyboard.pressed(key),
+            Binding::Mouse(_) if action.choice().is_some_and(|c| pointed != Some(c)) => false,
+            Binding::Mouse(button) if just => mouse.just_pressed(button),
+            Binding::Mouse(button) => mouse.pressed(button),
+            Binding::Pad(_) => false,
+        })
+    }
+
+    /// What one gamepad is doing this frame
+    pub fn read_pad(&self, pad: &Gamepad) -> ActionSet {
+        self.read(|_, binding, just| match binding {
+            Binding::Pad(button) if just => pad.just_pressed(button),
+            Binding::Pad(button) => pad.pressed(button),
+            _ => false,
+        })
+    }
+
+    fn read(&self, down: impl Fn(Action, Binding, bool) -> bool) -> ActionSet {
+        let mut set = ActionSet::default();
+        for (&action, bound) in &self.0 {
+            if bound.iter().any(|&b| down(action, b, true)) {
+                set.press(action);
+            }
+            if bound.iter().any(|&b| down(action, b, false)) {
+                set.held.insert(action);
+            }
+        }
+        set
+    }
+
+    /// The first binding for `action`, for on-screen hints
+    pub fn hint(&self, action: Action) -> String {
+        self.get(action).first().map_or_else(|| "-".to_string(), |b| b.label())
+    }
+}
+
+/// `bindings.ron` under the per-user config directory: `$XDG_CONFIG_HOME` or `~/.config` on
+/// Linux, `~/Library/Application Support` on macOS and `%APPDATA%` on Windows
+fn bindings_path() -> Option<PathBuf> {
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    let dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|h| h.join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home().map(|h| h.join(".config")))
+    };
+    dir.map(|d| d.join("two-people").join("bindings.ron"))
+}
+
+/// Actions from one source (the keyboard and mouse, or a single gamepad) this frame
+#[derive(Default)]
+pub struct ActionSet {
+    pressed: HashSet<Action>,
+    held: HashSet<Action>,
+}
+
+impl ActionSet {
+    pub fn press(&mut self, action: Action) {
+        self.pressed.insert(action);
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// The card picked this frame
+    pub fn choice(&self) -> Option<Choice> {
+        Action::ALL.into_iter().filter(|a| self.pressed.contains(a)).find_map(Action::choice)
+    }
+
+    /// The card whose choose binding is being held down
+    pub fn held_choice(&self) -> Option<Choice> {
+        Action::ALL.into_iter().filter(|a| self.held.contains(a)).find_map(Action::choice)
+    }
+
+    /// The card a couples player picked with their own binding this frame
+    fn player_choice(&self, player: usize) -> Option<Choice> {
+        Action::ALL
+            .into_iter()
+            .filter(|a| self.pressed.contains(a))
+            .find_map(|a| a.player_choice().filter(|&(p, _)| p == player).map(|(_, c)| c))
+    }
+}
+
+/// This frame's actions. Gamepads are kept apart, in connection order, so multiplayer modes
+/// can give each player their own.
+#[derive(Resource, Default)]
+pub struct Actions {
+    pub local: ActionSet,
+    pub pads: Vec<ActionSet>,
+    /// The card under the mouse pointer
+    pub pointed: Option<Choice>,
+}
+
+impl Actions {
+    fn sets(&self) -> impl Iterator<Item = &ActionSet> {
+        std::iter::once(&self.local).chain(&self.pads)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.sets().any(|s| s.just_pressed(action))
+    }
+
+    pub fn choice(&self) -> Option<Choice> {
+        self.sets().find_map(ActionSet::choice)
+    }
+
+    pub fn held_choice(&self) -> Option<Choice> {
+        self.sets().find_map(ActionSet::held_choice)
+    }
+
+    /// A couples player's pick: their half of the keyboard, or the choose buttons on their
+    /// own gamepad
+    pub fn player_choice(&self, player: usize) -> Option<Choice> {
+        self.sets()
+            .find_map(|s| s.player_choice(player))
+            .or_else(|| self.pads.get(player).and_then(ActionSet::choice))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The actions `binding` fires in `context` with the default bindings
+    fn fired(binding: Binding, context: Context) -> Vec<Action> {
+        let bindings = Bindings::default();
+        Action::ALL
+            .into_iter()
+            .filter(|&a| bindings.get(a).contains(&binding) && a.contexts(binding).contains(&context))
+            .collect()
+    }
+
+    #[test]
+    fn pad_start_restarts_from_the_results_and_pauses_elsewhere() {
+        let start = Binding::Pad(GamepadButton::Start);
+        assert!(fired(start, Context::Results) == [Action::Restart]);
+        assert!(fired(start, Context::Paused) == [Action::Pause]);
+        assert!(fired(start, Context::Solo) == [Action::Pause]);
+    }
+
+    #[test]
+    fn default_bindings_have_no_clashes() {
+        let mut bindings = Bindings::default();
+        let before = bindings.0.clone();
+        bindings.resolve_clashes();
+        assert!(bindings.0 == before);
+    }
+}
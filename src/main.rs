@@ -2,21 +2,50 @@
 //! CHOOSE FAST!
 
 use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
     audio::{PlaybackMode, Volume},
+    ecs::system::SystemParam,
+    input::{
+        gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest},
+        InputSystem,
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
     prelude::*,
-    window::PrimaryWindow,
+    sprite::Anchor,
+    text::{CosmicFontSystem, TextBounds},
+    window::{PrimaryWindow, WindowFocused},
 };
 use rand::Rng;
-use sqlx::{mysql::MySqlPoolOptions, MySqlPool, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::runtime::Runtime;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use tokio::sync::Mutex;
 
+mod controls;
+mod store;
+
+use controls::{Action, Actions, Binding, Bindings};
+
+use store::{ABANDONED, FinishedSession, Outcome, ResponseRecord, ScoreStore, ScoreWrite, StoreError, TraitScore, WriteQueue};
+
 // SETTINGS
 const WINDOW_WIDTH: f32 = 1280.0;
 const WINDOW_HEIGHT: f32 = 720.0;
 const QUESTION_TIME: f32 = 5.0;
 const HURRY_TIME: f32 = 2.0;
+/// Writes that couldn't be saved wait here until the store is reachable again
+const WRITE_QUEUE_PATH: &str = "pending_writes.jsonl";
+/// Reconnect backoff doubles from the first value up to the second
+const RECONNECT_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(60);
+/// How often a connected store is checked, and how long a check may take
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
 
 // COLORS - Vibrant!
 const BG_COLOR: Color = Color::srgb(0.06, 0.06, 0.10);
@@ -30,8 +59,30 @@ const RESULT_GREEN: Color = Color::srgb(0.2, 1.0, 0.5);
 // Sizes
 const CARD_W: f32 = 260.0;
 const CARD_H: f32 = 360.0;
+const CARD_RADIUS: f32 = 25.0;
 const CARD_GAP: f32 = 320.0;
 const HOVER_SCALE: f32 = 1.1;
+/// How far a dragged card has to travel, or how fast, before letting go picks it
+const FLING_DISTANCE: f32 = 160.0;
+const FLING_SPEED: f32 = 1200.0;
+/// Finger movement still counted as a tap
+const TAP_SLOP: f32 = 20.0;
+
+// Couples mode
+const REVEAL_TIME: f32 = 1.6;
+const PLAYER_NAMES: [&str; 2] = ["P1", "P2"];
+const PLAYER_COLORS: [Color; 2] = [TEXT_YELLOW, RESULT_GREEN];
+
+// Party mode
+const PARTY_MIN_PLAYERS: usize = 3;
+const PARTY_MAX_PLAYERS: usize = 8;
+const PARTY_NAME_LEN: usize = 10;
+/// Questions in each player's turn when the session length is endless
+const PARTY_ROUND: usize = 5;
+
+// Emoji glyphs are missing from the default font, so every text falls back to an emoji font:
+// the colour one that comes with the OS, or the bundled monochrome Noto Emoji without one.
+const COLOR_EMOJI_FAMILIES: [&str; 3] = ["Noto Color Emoji", "Apple Color Emoji", "Segoe UI Emoji"];
 
 // Components
 #[derive(Component)]
@@ -40,12 +91,22 @@ struct Card {
     base_y: f32,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 enum Choice {
     Left,
     Right,
 }
 
+impl Choice {
+    /// Where this side's card rests
+    fn card_x(self) -> f32 {
+        match self {
+            Choice::Left => -CARD_GAP / 2.0,
+            Choice::Right => CARD_GAP / 2.0,
+        }
+    }
+}
+
 #[derive(Component)]
 struct TitleText;
 #[derive(Component)]
@@ -58,6 +119,11 @@ struct ResultDisplay;
 struct CardLabel {
     choice: Choice,
 }
+/// Emoji drawn above the label, as a child of its card
+#[derive(Component)]
+struct CardEmoji {
+    choice: Choice,
+}
 #[derive(Component)]
 struct Particle {
     vel: Vec2,
@@ -93,16 +159,96 @@ struct StatsDisplay;
 #[derive(Component)]
 struct UhOhText;
 
-#[derive(Resource, Default)]
+#[derive(Component)]
+struct TraitSummary;
+
+#[derive(Component)]
+struct ConnectionIndicator;
+
+/// "7 / 20" in the top right while a session is running
+#[derive(Component)]
+struct ProgressText;
+
+/// Survival mode: one of the hearts under the progress counter
+#[derive(Component)]
+struct Heart {
+    index: i32,
+}
+
+/// Two-player modes: whose turn it is while answering, then their name on the card they picked
+#[derive(Component)]
+struct PlayerBadge {
+    player: usize,
+}
+
+/// Party mode: the names being entered, then who to pass the laptop to
+#[derive(Component)]
+struct RosterText;
+
+/// Party mode: one player's archetype on the results screen
+#[derive(Component)]
+struct PartyColumn;
+
+/// "MATCH!" / "NO MATCH" (or whether the guess was right) shown while a round is revealed
+#[derive(Component)]
+struct RevealText;
+
+#[derive(Resource, Default, Clone)]
 struct DbStats {
     loaded: bool,
     total_players: i64,
     avg_left_pct: f64,
+    /// Answer counts of the longest sessions, with whether each one is this session
+    top_runs: Vec<(i64, bool)>,
 }
 
 #[derive(Component)]
 struct GoText;
 
+#[derive(Component)]
+struct CategoryItem {
+    category: String,
+    index: usize,
+}
+
+#[derive(Component)]
+struct SettingsItem {
+    index: usize,
+}
+
+/// Dimmed backdrop and "PAUSED" header, shown while paused and in settings opened from there
+#[derive(Component)]
+struct PauseOverlay;
+
+#[derive(Component)]
+struct PauseItem;
+
+#[derive(Component)]
+struct MainMenuItem {
+    index: usize,
+}
+
+#[derive(Component)]
+struct ControlsItem {
+    index: usize,
+}
+
+/// Global stats on the STATS screen
+#[derive(Component)]
+struct StatsScreenText;
+
+#[derive(Component)]
+struct MenuButton {
+    action: MenuAction,
+    half_size: Vec2,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum MenuAction {
+    Start,
+    Back,
+}
+
 // Audio markers
 #[derive(Component)]
 struct BgMusic;
@@ -113,44 +259,311 @@ struct Game {
     phase: Phase,
     timer: f32,
     question: usize,
-    score_l: i32,
-    score_r: i32,
+    /// Everyone playing this session: one for solo, two for couples and guess, 3-8 at a party
+    players: Vec<PlayerScore>,
+    /// Party mode: index into `players` of whoever has the laptop
+    turn: usize,
+    /// Party mode: the questions drawn during the first turn, replayed for everyone else
+    party_questions: Vec<usize>,
+    /// Party mode: position of the current question in `party_questions`
+    party_pos: usize,
+    /// Questions shown this session (this turn, at a party), counting the one on screen
+    asked: usize,
     picked: Option<Choice>,
     wait: f32,
     session_id: String,
     last_tick: i32,
     hovered_card: Option<Choice>,
     results_shown: bool,
-    timeouts: i32,
+    /// Timeouts left before the session (or party turn) ends, see `Rules`
+    lives: i32,
+    /// Phase to go back to on resume, set while paused (and in settings opened from the pause menu)
+    paused_from: Option<Phase>,
+    /// "RESTART" was chosen on the pause menu, picked up by `handle_replay`
+    restart_requested: bool,
     streak: i32,
     used_questions: Vec<usize>,
     total_reaction_time: f32,
     answers_count: i32,
+    /// Answers and timeouts recorded this session, numbering the `responses` rows
+    responses_recorded: i32,
     last_reaction: f32,
     tremble: f32,
+    traits: HashMap<String, TraitTally>,
+    couples: CouplesTally,
+    guess: GuessTally,
+}
+
+/// One player's answers this session
+#[derive(Default)]
+struct PlayerScore {
+    name: String,
+    left: i32,
+    right: i32,
+    /// Answers in `left`/`right` that the timeout policy picked
+    auto_picks: i32,
+    /// Choice per question index, for comparing players who answered the same questions
+    answers: HashMap<usize, Choice>,
+}
+
+impl PlayerScore {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..default()
+        }
+    }
+
+    fn record(&mut self, question: usize, choice: Choice) {
+        match choice {
+            Choice::Left => self.left += 1,
+            Choice::Right => self.right += 1,
+        }
+        self.answers.insert(question, choice);
+    }
+
+    /// Percentage of the questions both players answered where they picked the same card
+    fn likeness(&self, other: &PlayerScore) -> Option<i32> {
+        let mut shared = 0;
+        let mut same = 0;
+        for (question, choice) in &self.answers {
+            if let Some(theirs) = other.answers.get(question) {
+                shared += 1;
+                same += (choice == theirs) as i32;
+            }
+        }
+        (shared > 0).then(|| (same * 100 + shared / 2) / shared)
+    }
+}
+
+/// Result title and `game_scores.result_type` for a left/right split
+fn archetype(left: i32, right: i32) -> (&'static str, &'static str) {
+    match left.cmp(&right) {
+        std::cmp::Ordering::Greater => ("CHAOTIC GREMLIN", "chaotic_gremlin"),
+        std::cmp::Ordering::Less => ("FUNCTIONING ADULT", "functioning_adult"),
+        std::cmp::Ordering::Equal => ("PERFECTLY BALANCED", "perfectly_balanced"),
+    }
+}
+
+/// Couples mode bookkeeping for the current session
+#[derive(Default)]
+struct CouplesTally {
+    /// Each partner's locked-in answer to the question on screen
+    picks: [Option<Choice>; 2],
+    /// Questions both partners answered, and how many of those they agreed on
+    answered: i32,
+    matches: i32,
+    /// (question, player one's answer, player two's answer) wherever they differed
+    disagreements: Vec<(String, String, String)>,
+}
+
+impl CouplesTally {
+    fn compatibility(&self) -> Option<i32> {
+        (self.answered > 0).then(|| (self.matches * 100 + self.answered / 2) / self.answered)
+    }
+}
+
+/// Guess-your-partner bookkeeping: one player answers in secret, the other predicts it
+#[derive(Default)]
+struct GuessTally {
+    /// Index into `PLAYER_NAMES` of this round's secret answerer; the other player predicts
+    answerer: usize,
+    secret: Option<Choice>,
+    prediction: Option<Choice>,
+    /// Per player: rounds they had to predict, and how many they got right
+    guesses: [i32; 2],
+    correct: [i32; 2],
+}
+
+impl GuessTally {
+    fn predictor(&self) -> usize {
+        1 - self.answerer
+    }
+
+    /// Answers on the cards indexed by player, as `CouplesTally::picks`
+    fn picks(&self) -> [Option<Choice>; 2] {
+        let mut picks = [None; 2];
+        picks[self.answerer] = self.secret;
+        picks[self.predictor()] = self.prediction;
+        picks
+    }
+
+    /// Clears the round and hands the secret answer to the other player
+    fn swap_roles(&mut self) {
+        self.answerer = self.predictor();
+        self.secret = None;
+        self.prediction = None;
+    }
+
+    /// How well the pair know each other: percentage of predictions that were right
+    fn knowledge(&self) -> Option<i32> {
+        let guesses: i32 = self.guesses.iter().sum();
+        let correct: i32 = self.correct.iter().sum();
+        (guesses > 0).then(|| (correct * 100 + guesses / 2) / guesses)
+    }
+}
+
+/// How often each option was picked across the questions sharing a `trait_name`. Keyed by
+/// the option's label, since those questions each have their own pair of options.
+#[derive(Default)]
+struct TraitTally(HashMap<String, i32>);
+
+impl TraitTally {
+    fn record(&mut self, label: String) {
+        *self.0.entry(label).or_default() += 1;
+    }
+
+    /// The option picked most often and how many times, `None` on a tie
+    fn dominant(&self) -> Option<(&str, i32)> {
+        let best = self.0.values().copied().max()?;
+        let mut top = self.0.iter().filter(|&(_, &n)| n == best);
+        let (label, _) = top.next()?;
+        top.next().is_none().then_some((label.as_str(), best))
+    }
+}
+
+impl Game {
+    /// Clears everything a session tallies for a new session with the same players, under a
+    /// new session id and with full lives. Leaves the phase and the question to the caller.
+    fn reset_for_session(&mut self, settings: &Settings) {
+        let players = self.players.iter().map(|p| PlayerScore::new(&p.name)).collect();
+        *self = Self {
+            phase: self.phase,
+            players,
+            used_questions: Vec::new(),
+            lives: settings.rules().lives,
+            ..default()
+        };
+    }
+
+    /// Applies the timeout rules to an answered question. Call after updating `streak`.
+    fn register_answer(&mut self, rules: &Rules) {
+        match rules.counting {
+            TimeoutCount::Consecutive => self.lives = rules.lives,
+            TimeoutCount::Total => {
+                if rules.regen_streak.is_some_and(|n| self.streak > 0 && self.streak % n == 0) {
+                    self.lives = (self.lives + 1).min(rules.lives);
+                }
+            }
+        }
+    }
+
+    /// Left and right answers across every player
+    fn totals(&self) -> (i32, i32) {
+        self.players.iter().fold((0, 0), |(l, r), p| (l + p.left, r + p.right))
+    }
+
+    fn auto_picks(&self) -> i32 {
+        self.players.iter().map(|p| p.auto_picks).sum()
+    }
+
+    /// Couples: how often the partners agreed; guess: how many predictions were right
+    fn compatibility(&self, mode: GameMode) -> Option<i32> {
+        match mode {
+            GameMode::Solo | GameMode::Party => None,
+            GameMode::Couples => self.couples.compatibility(),
+            GameMode::Guess => self.guess.knowledge(),
+        }
+    }
+
+    /// The session as it stands, for the score store
+    fn finished_session(&self, mode: GameMode, result_type: &str) -> FinishedSession {
+        let (score_left, score_right) = self.totals();
+        FinishedSession {
+            session_id: self.session_id.clone(),
+            mode: mode.code().to_string(),
+            score_left,
+            score_right,
+            auto_picks: self.auto_picks(),
+            result_type: result_type.to_string(),
+            compatibility: self.compatibility(mode),
+            traits: self
+                .dominant_traits()
+                .into_iter()
+                .map(|(name, value, score)| TraitScore {
+                    name: name.to_string(),
+                    value: value.chars().take(50).collect(),
+                    score,
+                })
+                .collect(),
+        }
+    }
+
+    /// Counts an answer to the current question for one player
+    fn record_answer(&mut self, player: usize, choice: Choice) {
+        let question = self.question;
+        if let Some(p) = self.players.get_mut(player) {
+            p.record(question, choice);
+        }
+    }
+
+    /// Pairs of players by how alike they answered, most alike first
+    fn alike_pairs(&self) -> Vec<(usize, usize, i32)> {
+        let mut pairs = Vec::new();
+        for a in 0..self.players.len() {
+            for b in a + 1..self.players.len() {
+                if let Some(pct) = self.players[a].likeness(&self.players[b]) {
+                    pairs.push((a, b, pct));
+                }
+            }
+        }
+        pairs.sort_by_key(|&(_, _, pct)| std::cmp::Reverse(pct));
+        pairs
+    }
+
+    fn record_trait(&mut self, q: &Q, choice: Choice) {
+        if q.trait_name.is_empty() {
+            return;
+        }
+        let label = match choice {
+            Choice::Left => &q.left,
+            Choice::Right => &q.right,
+        };
+        self.traits.entry(q.trait_name.clone()).or_default().record(label.replace('\n', " "));
+    }
+
+    /// Traits with a clear lean as (trait, option, answers), strongest first
+    fn dominant_traits(&self) -> Vec<(&str, &str, i32)> {
+        let mut out: Vec<(&str, &str, i32)> = self
+            .traits
+            .iter()
+            .filter_map(|(name, tally)| tally.dominant().map(|(value, n)| (name.as_str(), value, n)))
+            .collect();
+        out.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(b.0)));
+        out
+    }
 }
 
 impl Default for Game {
     fn default() -> Self {
         Self {
-            phase: Phase::Intro,
+            phase: Phase::MainMenu,
             timer: QUESTION_TIME,
             question: 0,
-            score_l: 0,
-            score_r: 0,
+            players: vec![PlayerScore::new("YOU")],
+            turn: 0,
+            party_questions: Vec::new(),
+            party_pos: 0,
+            asked: 1,
             picked: None,
             wait: 1.5,
             session_id: uuid::Uuid::new_v4().to_string(),
             last_tick: 5,
             hovered_card: None,
             results_shown: false,
-            timeouts: 0,
+            lives: Rules::CLASSIC.lives,
+            paused_from: None,
+            restart_requested: false,
             streak: 0,
             used_questions: vec![0],
             total_reaction_time: 0.0,
             answers_count: 0,
+            responses_recorded: 0,
             last_reaction: 5.0,
             tremble: 0.0,
+            traits: HashMap::new(),
+            couples: CouplesTally::default(),
+            guess: GuessTally::default(),
         }
     }
 }
@@ -158,557 +571,3180 @@ impl Default for Game {
 #[derive(Clone, Copy, PartialEq, Default)]
 enum Phase {
     #[default]
+    MainMenu,
+    Categories,
+    Settings,
+    /// Global stats from the score store, opened from the main menu
+    Stats,
+    /// Rebinding the input actions
+    Controls,
+    /// Party mode: typing in everyone's names
+    PartyNames,
+    /// Party mode: between turns, waiting for the next player to take over
+    Handover,
     Intro,
     Playing,
     Picked,
+    /// Guess mode: the answerer picks while the predictor looks away
+    SecretAnswer,
+    /// Guess mode: the predictor picks what they think the answerer chose
+    Predict,
+    /// Two-player modes: both answers are shown before moving on
+    Reveal,
     Transition,
     UhOh,
     Results,
+    /// Everything stops until the player resumes; the phase to go back to is `Game::paused_from`
+    Paused,
+}
+
+impl Phase {
+    /// A question is on screen and the timer is running
+    fn takes_answers(self) -> bool {
+        matches!(self, Phase::Playing | Phase::SecretAnswer | Phase::Predict)
+    }
+
+    /// Mid-session, where ESC or losing window focus pauses the game
+    fn pausable(self) -> bool {
+        self.takes_answers()
+            || matches!(
+                self,
+                Phase::Handover | Phase::Intro | Phase::Picked | Phase::Reveal | Phase::Transition | Phase::UhOh
+            )
+    }
 }
 
 #[derive(Resource)]
 struct Questions(Vec<Q>);
 
+#[derive(Clone, Deserialize)]
 struct Q {
-    title: &'static str,
-    left: &'static str,
-    left_em: &'static str,
-    right: &'static str,
-    right_em: &'static str,
-    trait_name: &'static str,
+    /// Row id in the `questions` table (or pack file), 0 for the built-in deck
+    id: i32,
+    title: String,
+    left: String,
+    #[serde(default)]
+    left_em: String,
+    right: String,
+    #[serde(default)]
+    right_em: String,
+    #[serde(default)]
+    trait_name: String,
+    category: String,
+    /// Explicit rating, otherwise derived from the category
+    #[serde(default)]
+    rating: Option<Rating>,
 }
 
-impl Default for Questions {
-    fn default() -> Self {
-        use rand::seq::SliceRandom;
-        let mut rng = rand::rng();
-        let mut all = vec![
-            Q { title: "There are 2 types of people...", left: "EARLY\nBIRD", left_em: "", right: "NIGHT\nOWL", right_em: "", trait_name: "" },
-            Q { title: "When the alarm goes off...", left: "SNOOZE\nx100", left_em: "", right: "UP &\nAT EM", right_em: "", trait_name: "" },
-            Q { title: "Your phone battery...", left: "5%\nALWAYS", left_em: "", right: "ALWAYS\n100%", right_em: "", trait_name: "" },
-            Q { title: "Texting back takes...", left: "3-5\nDAYS", left_em: "", right: "INSTANT\nREPLY", right_em: "", trait_name: "" },
-            Q { title: "Friday night = ...", left: "COUCH\nNETFLIX", left_em: "", right: "OUT\nTILL 4AM", right_em: "", trait_name: "" },
-            Q { title: "Lights on or off...", left: "LIGHTS\nON", left_em: "", right: "LIGHTS\nOFF", right_em: "", trait_name: "" },
-            Q { title: "Big spoon or...", left: "BIG\nSPOON", left_em: "", right: "LITTLE\nSPOON", right_em: "", trait_name: "" },
-            Q { title: "First date energy...", left: "NERVOUS\nWRECK", left_em: "", right: "MAIN\nCHARACTER", right_em: "", trait_name: "" },
-            Q { title: "Flirting style be like...", left: "EYE\nCONTACT", left_em: "", right: "JUST\nSAY IT", right_em: "", trait_name: "" },
-            Q { title: "When the vibe is off...", left: "GHOST\nTHEM", left_em: "", right: "TALK IT\nOUT", right_em: "", trait_name: "" },
-            Q { title: "Music during...", left: "YES\nALWAYS", left_em: "", right: "SILENCE\nIS GOLD", right_em: "", trait_name: "" },
-            Q { title: "Morning or night...", left: "SUNRISE\nENERGY", left_em: "", right: "AFTER\nDARK", right_em: "", trait_name: "" },
-            Q { title: "They ate your leftovers...", left: "WAR\nCRIME", left_em: "", right: "IT'S JUST\nFOOD", right_em: "", trait_name: "" },
-            Q { title: "Netflix and...", left: "ACTUALLY\nWATCH", left_em: "", right: "WHO'S\nWATCHING", right_em: "", trait_name: "" },
-            Q { title: "Pet names in public...", left: "BABY\nBABE", left_em: "", right: "FIRST\nNAME", right_em: "", trait_name: "" },
-            Q { title: "Thermostat wars...", left: "ARCTIC\nBLAST", left_em: "", right: "SAUNA\nMODE", right_em: "", trait_name: "" },
-            Q { title: "Road trip roles...", left: "DJ &\nNAVIGATOR", left_em: "", right: "DRIVER\nONLY", right_em: "", trait_name: "" },
-            Q { title: "Saying I love you...", left: "EVERY\n5 MIN", left_em: "", right: "WHEN IT\nMATTERS", right_em: "", trait_name: "" },
-            Q { title: "Going to bed angry...", left: "NEVER\nEVER", left_em: "", right: "SLEEP\nON IT", right_em: "", trait_name: "" },
-            Q { title: "Drunk behavior...", left: "CLINGY\nAF", left_em: "", right: "SLEEPY\nQUIET", right_em: "", trait_name: "" },
-        ];
-        all.shuffle(&mut rng);
-        Self(all)
-    }
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Rating {
+    Family,
+    Mature,
 }
 
-#[derive(Resource)]
-struct GameSounds {
-    hover: Handle<AudioSource>,
-    click: Handle<AudioSource>,
-    tick: Handle<AudioSource>,
-    tick_urgent: Handle<AudioSource>,
-    whoosh: Handle<AudioSource>,
-    result: Handle<AudioSource>,
-    select: Handle<AudioSource>,
-    go: Handle<AudioSource>,
-    card_in: Handle<AudioSource>,
+// Categories that are never shown in family-safe mode
+const MATURE_CATEGORIES: &[&str] = &["spicy", "intimate"];
+
+impl Q {
+    fn option(&self, choice: Choice) -> &str {
+        match choice {
+            Choice::Left => &self.left,
+            Choice::Right => &self.right,
+        }
+    }
+
+    fn emoji(&self, choice: Choice) -> &str {
+        match choice {
+            Choice::Left => &self.left_em,
+            Choice::Right => &self.right_em,
+        }
+    }
+
+    fn rating(&self) -> Rating {
+        self.rating.unwrap_or_else(|| {
+            if MATURE_CATEGORIES.contains(&self.category.as_str()) {
+                Rating::Mature
+            } else {
+                Rating::Family
+            }
+        })
+    }
 }
 
-#[derive(Resource)]
-struct DbPool(Arc<Mutex<Option<MySqlPool>>>);
+// Built-in deck, used when there is no database (title, left, left_em, right, right_em, trait, category)
+const BUILTIN_QUESTIONS: &[(&str, &str, &str, &str, &str, &str, &str)] = &[
+    ("There are 2 types of people...", "EARLY\nBIRD", "🌅", "NIGHT\nOWL", "🌙", "sleep_schedule", "lifestyle"),
+    ("When the alarm goes off...", "SNOOZE\nx100", "😴", "UP &\nAT EM", "⏰", "morning_vibe", "lifestyle"),
+    ("Your phone battery...", "5%\nALWAYS", "😱", "ALWAYS\n100%", "🔋", "battery_life", "lifestyle"),
+    ("Texting back takes...", "3-5\nDAYS", "🐢", "INSTANT\nREPLY", "⚡", "texting", "honest"),
+    ("Friday night = ...", "COUCH\nNETFLIX", "📺", "OUT\nTILL 4AM", "🎉", "weekend_plans", "lifestyle"),
+    ("Lights on or off...", "LIGHTS\nON", "💡", "LIGHTS\nOFF", "🌚", "ambiance", "spicy"),
+    ("Big spoon or...", "BIG\nSPOON", "🐻", "LITTLE\nSPOON", "🐣", "cuddle_position", "romantic"),
+    ("First date energy...", "NERVOUS\nWRECK", "😰", "MAIN\nCHARACTER", "💅", "confidence", "flirty"),
+    ("Flirting style be like...", "EYE\nCONTACT", "👀", "JUST\nSAY IT", "📢", "flirt_style", "flirty"),
+    ("When the vibe is off...", "GHOST\nTHEM", "👻", "TALK IT\nOUT", "💬", "conflict_style", "honest"),
+    ("Music during...", "YES\nALWAYS", "🎶", "SILENCE\nIS GOLD", "🔇", "soundtrack", "spicy"),
+    ("Morning or night...", "SUNRISE\nENERGY", "☀️", "AFTER\nDARK", "🌃", "sleep_schedule", "lifestyle"),
+    ("They ate your leftovers...", "WAR\nCRIME", "💣", "IT'S JUST\nFOOD", "🍕", "sharing", "silly"),
+    ("Netflix and...", "ACTUALLY\nWATCH", "📺", "WHO'S\nWATCHING", "😏", "date_preference", "spicy"),
+    ("Pet names in public...", "BABY\nBABE", "😘", "FIRST\nNAME", "👋", "pda_comfort", "romantic"),
+    ("Thermostat wars...", "ARCTIC\nBLAST", "❄️", "SAUNA\nMODE", "🔥", "temperature", "silly"),
+    ("Road trip roles...", "DJ &\nNAVIGATOR", "🎧", "DRIVER\nONLY", "🚗", "road_trip", "lifestyle"),
+    ("Saying I love you...", "EVERY\n5 MIN", "💕", "WHEN IT\nMATTERS", "💍", "affection", "romantic"),
+    ("Going to bed angry...", "NEVER\nEVER", "😤", "SLEEP\nON IT", "😴", "conflict_style", "honest"),
+    ("Drunk behavior...", "CLINGY\nAF", "🐨", "SLEEPY\nQUIET", "💤", "drunk_energy", "silly"),
+];
 
-impl Default for DbPool {
+impl Default for Questions {
     fn default() -> Self {
-        Self(Arc::new(Mutex::new(None)))
+        let all = BUILTIN_QUESTIONS
+            .iter()
+            .map(|&(title, left, left_em, right, right_em, trait_name, category)| Q {
+                id: 0,
+                title: title.into(),
+                left: left.into(),
+                left_em: left_em.into(),
+                right: right.into(),
+                right_em: right_em.into(),
+                trait_name: trait_name.into(),
+                category: category.into(),
+                rating: None,
+            })
+            .collect();
+        Self::shuffled(all)
     }
 }
 
-#[derive(Resource)]
-struct TokioRuntime(Runtime);
+impl Questions {
+    fn shuffled(mut all: Vec<Q>) -> Self {
+        use rand::seq::SliceRandom;
+        all.shuffle(&mut rand::rng());
+        Self(all)
+    }
 
-// Events for audio playback
-#[derive(Event)]
-struct PlaySoundEvent(SoundType);
+    /// Titles of the questions at `indices`, to find them again after a reload
+    fn titles(&self, indices: &[usize]) -> Vec<String> {
+        indices.iter().filter_map(|&i| self.0.get(i)).map(|q| q.title.clone()).collect()
+    }
 
-#[derive(Event)]
-struct SpawnFireworksEvent {
-    x: f32,
-    intensity: i32,
+    /// Where the questions with `titles` ended up, skipping any that are gone
+    fn positions(&self, titles: &[String]) -> Vec<usize> {
+        titles.iter().filter_map(|title| self.0.iter().position(|q| q.title == *title)).collect()
+    }
 }
 
-#[derive(Clone, Copy)]
-enum SoundType {
-    Hover,
-    Click,
-    Tick,
-    TickUrgent,
-    Whoosh,
-    Result,
-    Select,
-    Go,
-    CardIn,
+/// Categories switched off in the pre-game menu; anything not listed is in the session deck
+#[derive(Resource, Default)]
+struct SessionCategories {
+    disabled: HashSet<String>,
 }
 
-/// Creates a rounded rectangle mesh for cards
-fn create_rounded_rect_mesh(width: f32, height: f32, radius: f32) -> Mesh {
-    use bevy::render::mesh::{Indices, PrimitiveTopology};
-    
-    let hw = width / 2.0;
-    let hh = height / 2.0;
-    let r = radius.min(hw).min(hh); // Clamp radius
-    let segments = 8; // Segments per corner
-    
-    let mut positions: Vec<[f32; 3]> = Vec::new();
-    let mut uvs: Vec<[f32; 2]> = Vec::new();
-    let mut indices: Vec<u32> = Vec::new();
-    
-    // Center vertex
-    positions.push([0.0, 0.0, 0.0]);
-    uvs.push([0.5, 0.5]);
-    
-    // Generate vertices around the rounded rectangle
-    let corners = [
-        (hw - r, hh - r, 0.0),              // Top-right
-        (-hw + r, hh - r, std::f32::consts::FRAC_PI_2),  // Top-left
-        (-hw + r, -hh + r, std::f32::consts::PI),        // Bottom-left
-        (hw - r, -hh + r, std::f32::consts::PI * 1.5),   // Bottom-right
-    ];
-    
-    for (cx, cy, start_angle) in corners {
-        for i in 0..=segments {
-            let angle = start_angle + (i as f32 / segments as f32) * std::f32::consts::FRAC_PI_2;
-            let x = cx + r * angle.cos();
-            let y = cy + r * angle.sin();
-            positions.push([x, y, 0.0]);
-            uvs.push([(x / width) + 0.5, (y / height) + 0.5]);
+impl SessionCategories {
+    fn allows(&self, q: &Q) -> bool {
+        !self.disabled.contains(&q.category)
+    }
+}
+
+/// Who is playing: one person with the mouse, two partners sharing the keyboard, two
+/// partners taking turns to predict each other, or a party passing the mouse around
+#[derive(Clone, Copy, PartialEq, Default)]
+enum GameMode {
+    #[default]
+    Solo,
+    Couples,
+    Guess,
+    Party,
+}
+
+impl GameMode {
+    /// Stored in `game_scores.mode`
+    fn code(self) -> &'static str {
+        match self {
+            GameMode::Solo => "solo",
+            GameMode::Couples => "couples",
+            GameMode::Guess => "guess",
+            GameMode::Party => "party",
         }
     }
-    
-    // Generate triangle fan indices
-    let num_outer = positions.len() as u32 - 1;
-    for i in 1..=num_outer {
-        let next = if i == num_outer { 1 } else { i + 1 };
-        indices.extend_from_slice(&[0, i, next]);
+
+    fn next(self) -> Self {
+        match self {
+            GameMode::Solo => GameMode::Couples,
+            GameMode::Couples => GameMode::Guess,
+            GameMode::Guess => GameMode::Party,
+            GameMode::Party => GameMode::Solo,
+        }
     }
-    
-    Mesh::new(PrimitiveTopology::TriangleList, default())
-        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
-        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
-        .with_inserted_indices(Indices::U32(indices))
-}
 
-fn main() {
-    // Load env vars
-    let _ = dotenvy::dotenv();
+    /// One player answers at a time with the mouse
+    fn clicks_cards(self) -> bool {
+        matches!(self, GameMode::Solo | GameMode::Party)
+    }
 
-    // Create tokio runtime for async DB operations
-    let runtime = Runtime::new().expect("Failed to create Tokio runtime");
+    /// Fresh scores for everyone taking part
+    fn players(self, roster: &PartyRoster) -> Vec<PlayerScore> {
+        match self {
+            GameMode::Solo => vec![PlayerScore::new("YOU")],
+            GameMode::Couples | GameMode::Guess => PLAYER_NAMES.iter().map(|n| PlayerScore::new(n)).collect(),
+            GameMode::Party => roster.names.iter().map(|n| PlayerScore::new(n)).collect(),
+        }
+    }
 
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "TWO PEOPLE - CHOOSE FAST!".into(),
+    /// Phase each new question starts in
+    fn question_phase(self) -> Phase {
+        match self {
+            GameMode::Guess => Phase::SecretAnswer,
+            _ => Phase::Playing,
+        }
+    }
+
+    /// Bottom-of-screen hint while a question is up
+    fn play_hint(self, bindings: &Bindings) -> String {
+        let keys = |left, right| format!("{} / {}", bindings.hint(left), bindings.hint(right));
+        let choose = keys(Action::ChooseLeft, Action::ChooseRight);
+        match self {
+            GameMode::Solo => format!("Click a card or press {} to choose!", choose),
+            GameMode::Couples => format!(
+                "P1: {}    P2: {}    Answers stay hidden!",
+                keys(Action::P1Left, Action::P1Right),
+                keys(Action::P2Left, Action::P2Right)
+            ),
+            GameMode::Guess => "One answers in secret, the other guesses. Click a card!".into(),
+            GameMode::Party => format!("Click a card or press {}! Then pass it on", choose),
+        }
+    }
+}
+
+/// How many questions a session runs for
+#[derive(Clone, Copy, PartialEq, Default)]
+enum SessionLength {
+    Ten,
+    #[default]
+    Twenty,
+    Fifty,
+    Endless,
+}
+
+impl SessionLength {
+    fn limit(self) -> Option<usize> {
+        match self {
+            SessionLength::Ten => Some(10),
+            SessionLength::Twenty => Some(20),
+            SessionLength::Fifty => Some(50),
+            SessionLength::Endless => None,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            SessionLength::Ten => SessionLength::Twenty,
+            SessionLength::Twenty => SessionLength::Fifty,
+            SessionLength::Fifty => SessionLength::Endless,
+            SessionLength::Endless => SessionLength::Ten,
+        }
+    }
+
+    /// Answers before the time pressure kicks in: the last 40% of a fixed-length session,
+    /// or after 20 answers in an endless one
+    fn pressure_start(self) -> i32 {
+        match self.limit() {
+            Some(n) => (n - n * 2 / 5) as i32,
+            None => 20,
+        }
+    }
+}
+
+/// Which timeouts count against `Rules::lives`
+#[derive(Clone, Copy, PartialEq)]
+enum TimeoutCount {
+    /// Only timeouts in a row; any answer restores every life
+    Consecutive,
+    /// Every timeout this session
+    Total,
+}
+
+/// How timeouts end a session
+#[derive(Clone, Copy, PartialEq)]
+struct Rules {
+    lives: i32,
+    /// Answers in a row that win back a lost life, `None` if lives never come back
+    regen_streak: Option<i32>,
+    counting: TimeoutCount,
+    /// Runs until the last life is gone, whatever the session length
+    ignores_length: bool,
+    /// Lives are shown as hearts
+    show_hearts: bool,
+}
+
+impl Rules {
+    /// Three timeouts in a row end the session
+    const CLASSIC: Rules = Rules {
+        lives: 3,
+        regen_streak: None,
+        counting: TimeoutCount::Consecutive,
+        ignores_length: false,
+        show_hearts: false,
+    };
+
+    /// Three lives for the whole session, one back for every five answers in a row
+    const SURVIVAL: Rules = Rules {
+        lives: 3,
+        regen_streak: Some(5),
+        counting: TimeoutCount::Total,
+        ignores_length: true,
+        show_hearts: true,
+    };
+}
+
+/// Named rule sets offered on the settings screen
+#[derive(Clone, Copy, PartialEq, Default)]
+enum RuleSet {
+    #[default]
+    Classic,
+    Survival,
+}
+
+impl RuleSet {
+    fn rules(self) -> Rules {
+        match self {
+            RuleSet::Classic => Rules::CLASSIC,
+            RuleSet::Survival => Rules::SURVIVAL,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            RuleSet::Classic => RuleSet::Survival,
+            RuleSet::Survival => RuleSet::Classic,
+        }
+    }
+
+    fn label(self) -> String {
+        let rules = self.rules();
+        match self {
+            RuleSet::Classic => format!("RULES: CLASSIC ({} TIMEOUTS IN A ROW)", rules.lives),
+            RuleSet::Survival => format!(
+                "RULES: SURVIVAL ({} LIVES, +1 PER {} STREAK)",
+                rules.lives,
+                rules.regen_streak.unwrap_or(0)
+            ),
+        }
+    }
+}
+
+/// What happens to a solo or party question nobody answered in time
+#[derive(Clone, Copy, PartialEq, Default)]
+enum TimeoutPolicy {
+    /// No answer is recorded
+    #[default]
+    Skip,
+    Random,
+    /// The card under the cursor, the option the player was leaning to; skips if neither is
+    Hovered,
+}
+
+impl TimeoutPolicy {
+    fn pick(self, hovered: Option<Choice>) -> Option<Choice> {
+        match self {
+            TimeoutPolicy::Skip => None,
+            TimeoutPolicy::Random => Some(if rand::rng().random_bool(0.5) { Choice::Left } else { Choice::Right }),
+            TimeoutPolicy::Hovered => hovered,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            TimeoutPolicy::Skip => TimeoutPolicy::Random,
+            TimeoutPolicy::Random => TimeoutPolicy::Hovered,
+            TimeoutPolicy::Hovered => TimeoutPolicy::Skip,
+        }
+    }
+}
+
+/// Player-facing options, changed on the settings screen
+#[derive(Resource)]
+struct Settings {
+    family_safe: bool,
+    mode: GameMode,
+    length: SessionLength,
+    rule_set: RuleSet,
+    on_timeout: TimeoutPolicy,
+    /// Toggled by the MUTE action rather than a settings row
+    muted: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            family_safe: std::env::var("CONTENT_RATING").is_ok_and(|r| r.eq_ignore_ascii_case("family")),
+            mode: GameMode::default(),
+            length: SessionLength::default(),
+            rule_set: RuleSet::default(),
+            on_timeout: TimeoutPolicy::default(),
+            muted: false,
+        }
+    }
+}
+
+impl Settings {
+    fn allows(&self, q: &Q) -> bool {
+        !self.family_safe || q.rating() == Rating::Family
+    }
+
+    /// One line per setting, in the order shown on the settings screen
+    fn rows(&self, bindings: &Bindings) -> Vec<String> {
+        vec![
+            format!("FAMILY-SAFE MODE: {}", if self.family_safe { "ON" } else { "OFF" }),
+            match self.mode {
+                GameMode::Solo => "MODE: SOLO".into(),
+                GameMode::Couples => format!(
+                    "MODE: COUPLES ({}/{} vs {}/{})",
+                    bindings.hint(Action::P1Left),
+                    bindings.hint(Action::P1Right),
+                    bindings.hint(Action::P2Left),
+                    bindings.hint(Action::P2Right)
+                ),
+                GameMode::Guess => "MODE: GUESS YOUR PARTNER".into(),
+                GameMode::Party => format!("MODE: PARTY ({}-{} PLAYERS)", PARTY_MIN_PLAYERS, PARTY_MAX_PLAYERS),
+            },
+            match self.length.limit() {
+                Some(n) if self.mode == GameMode::Party => format!("SESSION LENGTH: {} QUESTIONS EACH", n),
+                Some(n) => format!("SESSION LENGTH: {} QUESTIONS", n),
+                None => "SESSION LENGTH: ENDLESS".into(),
+            },
+            self.rule_set.label(),
+            match self.on_timeout {
+                TimeoutPolicy::Skip => "ON TIMEOUT: SKIP THE QUESTION".into(),
+                TimeoutPolicy::Random => "ON TIMEOUT: PICK AT RANDOM".into(),
+                TimeoutPolicy::Hovered => "ON TIMEOUT: PICK THE HOVERED CARD".into(),
+            },
+        ]
+    }
+
+    /// Steps the setting on the given row to its next value
+    fn change(&mut self, index: usize) {
+        match index {
+            0 => self.family_safe = !self.family_safe,
+            1 => self.mode = self.mode.next(),
+            2 => self.length = self.length.next(),
+            3 => self.rule_set = self.rule_set.next(),
+            4 => self.on_timeout = self.on_timeout.next(),
+            _ => {}
+        }
+    }
+}
+
+/// A card being dragged by a finger on a touchscreen
+#[derive(Resource, Default)]
+struct TouchDrag {
+    touch: Option<u64>,
+    card: Option<Choice>,
+    /// World position where the finger went down
+    start: Vec2,
+    /// How far the card has been pulled from its spot, eased back to zero after a weak fling
+    offset: Vec2,
+    velocity: Vec2,
+    /// A tap or strong fling waiting for `read_actions`
+    picked: Option<Choice>,
+}
+
+/// Controls screen: the highlighted action, and whether the next press gets bound to it
+#[derive(Resource, Default)]
+struct ControlsMenu {
+    cursor: usize,
+    /// First row on screen, when there are more rows than fit
+    scroll: usize,
+    /// Seconds left to press something for the highlighted action. Every key and button can
+    /// be bound, so waiting this out is how to cancel.
+    listening: Option<f32>,
+}
+
+impl ControlsMenu {
+    fn move_to(&mut self, row: usize) {
+        self.cursor = row;
+        self.scroll = self.scroll.clamp((row + 1).saturating_sub(CONTROLS_VISIBLE), row);
+    }
+}
+
+/// Controls screen rows: one per action, then a reset
+const CONTROLS_ROWS: usize = Action::ALL.len() + 1;
+/// Rows shown at once, leaving room for the BACK button
+const CONTROLS_VISIBLE: usize = 9;
+const LISTEN_TIME: f32 = 5.0;
+
+/// Highlighted row on the main menu, moved by keyboard, gamepad or mouse
+#[derive(Resource, Default)]
+struct MainMenuCursor(usize);
+
+const MAIN_MENU_ROWS: usize = 7;
+
+/// Main menu rows, top to bottom: play, mode, categories, settings, controls, stats, quit
+fn main_menu_rows(qs: &Questions, cats: &SessionCategories, settings: &Settings) -> [String; MAIN_MENU_ROWS] {
+    let deck = deck_categories(qs, settings);
+    let enabled = deck.iter().filter(|(c, _)| !cats.disabled.contains(c)).count();
+    let mode = match settings.mode {
+        GameMode::Solo => "SOLO",
+        GameMode::Couples => "COUPLES",
+        GameMode::Guess => "GUESS YOUR PARTNER",
+        GameMode::Party => "PARTY",
+    };
+    [
+        "PLAY".into(),
+        format!("< MODE: {} >", mode),
+        format!("CATEGORIES ({} OF {})", enabled, deck.len()),
+        "SETTINGS".into(),
+        "CONTROLS".into(),
+        "STATS".into(),
+        "QUIT".into(),
+    ]
+}
+
+/// Sets up a new session from the menus. Party mode goes to name entry first, everything
+/// else to the intro. Returns false when no question matches the enabled categories.
+fn begin_session(game: &mut Game, qs: &Questions, cats: &SessionCategories, settings: &Settings) -> bool {
+    if settings.mode == GameMode::Party {
+        game.phase = Phase::PartyNames;
+        return true;
+    }
+    game.players = settings.mode.players(&PartyRoster::default());
+    game.reset_for_session(settings);
+    let Some(first) = next_question(qs, game, cats, settings) else {
+        return false;
+    };
+    game.question = first;
+    game.used_questions.push(first);
+    game.phase = Phase::Intro;
+    game.wait = 1.5;
+    true
+}
+
+/// Bottom-of-screen hint once `begin_session` has run
+fn session_hint(game: &Game, settings: &Settings, bindings: &Bindings) -> String {
+    if game.phase == Phase::PartyNames {
+        PARTY_NAMES_HINT.into()
+    } else {
+        settings.mode.play_hint(bindings)
+    }
+}
+
+/// Party mode names, kept between sessions so the same group can replay
+#[derive(Resource, Default)]
+struct PartyRoster {
+    names: Vec<String>,
+    /// Name being typed
+    typing: String,
+}
+
+impl PartyRoster {
+    fn ready(&self) -> bool {
+        self.names.len() >= PARTY_MIN_PLAYERS
+    }
+}
+
+impl Settings {
+    fn rules(&self) -> Rules {
+        self.rule_set.rules()
+    }
+
+    /// Questions in a session, or in each party turn; `None` runs until the deck is used up
+    /// or the lives run out
+    fn questions_per_run(&self) -> Option<usize> {
+        match self.mode {
+            GameMode::Party => Some(self.length.limit().unwrap_or(PARTY_ROUND)),
+            _ if self.rules().ignores_length => None,
+            _ => self.length.limit(),
+        }
+    }
+}
+
+/// Every category with questions allowed by the content rating, with counts, sorted by name
+fn deck_categories(qs: &Questions, settings: &Settings) -> Vec<(String, usize)> {
+    let mut cats: Vec<(String, usize)> = Vec::new();
+    for q in qs.0.iter().filter(|q| settings.allows(q)) {
+        match cats.iter_mut().find(|(c, _)| *c == q.category) {
+            Some((_, n)) => *n += 1,
+            None => cats.push((q.category.clone(), 1)),
+        }
+    }
+    cats.sort();
+    cats
+}
+
+/// Picks a random unused question from the enabled categories and content rating.
+/// Every path that moves to a new question goes through here.
+fn next_question(
+    qs: &Questions,
+    game: &Game,
+    cats: &SessionCategories,
+    settings: &Settings,
+) -> Option<usize> {
+    let available: Vec<usize> = (0..qs.0.len())
+        .filter(|i| {
+            let q = &qs.0[*i];
+            !game.used_questions.contains(i) && cats.allows(q) && settings.allows(q)
+        })
+        .collect();
+    if available.is_empty() {
+        return None;
+    }
+    Some(available[rand::rng().random_range(0..available.len())])
+}
+
+/// The question after this one: a fresh draw, or in party mode the next of the set drawn
+/// during the first turn. `None` ends the session (or the current party turn) once the
+/// session length is reached or the deck runs out.
+fn advance_question(
+    qs: &Questions,
+    game: &mut Game,
+    cats: &SessionCategories,
+    settings: &Settings,
+) -> Option<usize> {
+    if settings.questions_per_run().is_some_and(|n| game.asked >= n) {
+        return None;
+    }
+    game.asked += 1;
+    if settings.mode == GameMode::Party {
+        game.party_pos += 1;
+        if game.turn > 0 {
+            return game.party_questions.get(game.party_pos).copied();
+        }
+    }
+    let next = next_question(qs, game, cats, settings)?;
+    game.used_questions.push(next);
+    if settings.mode == GameMode::Party {
+        game.party_questions.push(next);
+    }
+    Some(next)
+}
+
+/// Where to go when the questions run out: the next party player, or the results
+fn end_turn(game: &mut Game, settings: &Settings) -> Phase {
+    if settings.mode != GameMode::Party || game.turn + 1 >= game.players.len() {
+        return Phase::Results;
+    }
+    game.turn += 1;
+    game.party_pos = 0;
+    game.asked = 1;
+    game.question = game.party_questions.first().copied().unwrap_or(game.question);
+    Phase::Handover
+}
+
+/// Where the current deck came from. Only database questions have a `questions` row to reference.
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+enum DeckSource {
+    #[default]
+    BuiltIn,
+    Database,
+    Pack,
+}
+
+/// Deck fetched from the `questions` table, ready to replace the built-in one
+#[derive(Event)]
+struct QuestionsLoaded(Vec<Q>);
+
+/// A deck of questions loaded from `assets/packs/*.pack.ron`
+#[derive(Asset, TypePath, Deserialize)]
+struct QuestionPack {
+    name: String,
+    questions: Vec<Q>,
+}
+
+#[derive(Default)]
+struct QuestionPackLoader;
+
+impl AssetLoader for QuestionPackLoader {
+    type Asset = QuestionPack;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<QuestionPack, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pack.ron"]
+    }
+}
+
+/// The pack picked with `QUESTION_PACK`, which takes precedence over the database deck
+#[derive(Resource)]
+struct ActivePack(Handle<QuestionPack>);
+
+#[derive(Resource)]
+struct GameSounds {
+    hover: Handle<AudioSource>,
+    click: Handle<AudioSource>,
+    tick: Handle<AudioSource>,
+    tick_urgent: Handle<AudioSource>,
+    whoosh: Handle<AudioSource>,
+    result: Handle<AudioSource>,
+    select: Handle<AudioSource>,
+    go: Handle<AudioSource>,
+    card_in: Handle<AudioSource>,
+}
+
+/// The backend picked with `DATABASE_URL`, once it has been opened
+#[derive(Resource)]
+struct ActiveStore {
+    store: Arc<Mutex<Option<Arc<dyn ScoreStore>>>>,
+    /// Only kept when there is a `DATABASE_URL` that queued writes could eventually reach
+    queue: Option<Arc<WriteQueue>>,
+}
+
+impl Default for ActiveStore {
+    fn default() -> Self {
+        Self {
+            store: Arc::new(Mutex::new(None)),
+            queue: std::env::var("DATABASE_URL")
+                .is_ok()
+                .then(|| Arc::new(WriteQueue::new(WRITE_QUEUE_PATH))),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct TokioRuntime(Runtime);
+
+/// Where the score store connection is, shown in the corner so operators know results are saved
+#[derive(Resource, Clone, Copy, PartialEq, Default)]
+enum ConnectionState {
+    /// No `DATABASE_URL`, or the connection just dropped and a retry is about to start
+    #[default]
+    Disconnected,
+    Connecting,
+    Connected(&'static str),
+    /// The last attempt failed; the next one starts after `retry_secs`
+    Failed { retry_secs: u64 },
+}
+
+impl ConnectionState {
+    fn label(&self) -> String {
+        match self {
+            ConnectionState::Disconnected => "OFFLINE".into(),
+            ConnectionState::Connecting => "CONNECTING...".into(),
+            ConnectionState::Connected(backend) => format!("SAVING TO {}", backend.to_uppercase()),
+            ConnectionState::Failed { retry_secs } => format!("NO DATABASE - RETRY IN {}s", retry_secs),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            ConnectionState::Disconnected => Color::srgba(1.0, 1.0, 1.0, 0.4),
+            ConnectionState::Connecting => Color::srgba(1.0, 0.85, 0.2, 0.8),
+            ConnectionState::Connected(_) => Color::srgba(0.2, 1.0, 0.5, 0.6),
+            ConnectionState::Failed { .. } => Color::srgba(1.0, 0.3, 0.3, 0.9),
+        }
+    }
+}
+
+/// Outcome of a background database job, handed to the ECS by `drain_db_results`
+enum DbResult {
+    Questions(Vec<Q>),
+    Stats(DbStats),
+    Saved(&'static str),
+    Queued { job: &'static str, error: String },
+    /// The store refused a write and it went to the dead-letter file
+    Rejected {
+        job: &'static str,
+        error: String,
+        moved_to: std::path::PathBuf,
+    },
+    Replayed(usize),
+    Connection(ConnectionState),
+    Failed { job: &'static str, error: String },
+}
+
+#[derive(Resource)]
+struct DbResultSender(UnboundedSender<DbResult>);
+
+#[derive(Resource)]
+struct DbResultReceiver(UnboundedReceiver<DbResult>);
+
+/// Everything a system needs to run database work off the main thread
+#[derive(SystemParam)]
+struct Db<'w> {
+    store: Res<'w, ActiveStore>,
+    runtime: Res<'w, TokioRuntime>,
+    results: Res<'w, DbResultSender>,
+}
+
+impl Db<'_> {
+    fn write(&self, write: ScoreWrite) {
+        let kind = write.kind();
+        self.write_then(write, move |_| async move { Ok(DbResult::Saved(kind)) });
+    }
+
+    /// Saves `write`, queueing it on disk when there is no store yet or the write fails.
+    /// A write the store refuses outright is set aside in the dead-letter file rather than
+    /// queued, since replaying it would only fail again. `then` only runs once the write has landed.
+    fn write_then<F, Fut>(&self, write: ScoreWrite, then: F)
+    where
+        F: FnOnce(Arc<dyn ScoreStore>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<DbResult, StoreError>> + Send,
+    {
+        let store_arc = self.store.store.clone();
+        let queue = self.store.queue.clone();
+        let tx = self.results.0.clone();
+        self.runtime.0.spawn(async move {
+            let kind = write.kind();
+            let store = store_arc.lock().await.clone();
+            let error = match store {
+                Some(store) => match store.apply(write.clone()).await {
+                    Ok(()) => {
+                        if let Some(queue) = &queue {
+                            replay_queue(queue, store.as_ref(), &tx).await;
+                        }
+                        let result = then(store).await.unwrap_or_else(|e| DbResult::Failed {
+                            job: kind,
+                            error: e.to_string(),
+                        });
+                        // The receiver only goes away when the app exits
+                        let _ = tx.send(result);
+                        return;
+                    }
+                    Err(e) if e.is_transient() => e.to_string(),
+                    Err(e) => {
+                        let result = match &queue {
+                            Some(queue) => match queue.reject(write, &e).await {
+                                Ok(()) => DbResult::Rejected {
+                                    job: kind,
+                                    error: e.to_string(),
+                                    moved_to: queue.rejected_path().to_path_buf(),
+                                },
+                                Err(e2) => DbResult::Failed {
+                                    job: kind,
+                                    error: format!("{}, and setting it aside failed: {}", e, e2),
+                                },
+                            },
+                            None => DbResult::Failed {
+                                job: kind,
+                                error: e.to_string(),
+                            },
+                        };
+                        let _ = tx.send(result);
+                        return;
+                    }
+                },
+                None => "not connected".to_string(),
+            };
+
+            let result = match queue {
+                Some(queue) => match queue.push(write).await {
+                    Ok(()) => DbResult::Queued { job: kind, error },
+                    Err(e) => DbResult::Failed {
+                        job: kind,
+                        error: format!("{}, and queueing it failed: {}", error, e),
+                    },
+                },
+                None => return,
+            };
+            let _ = tx.send(result);
+        });
+    }
+}
+
+impl Db<'_> {
+    /// Runs a read-only job against the store, if one is connected
+    fn read<F, Fut>(&self, job: &'static str, f: F)
+    where
+        F: FnOnce(Arc<dyn ScoreStore>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<DbResult, StoreError>> + Send,
+    {
+        let store_arc = self.store.store.clone();
+        let tx = self.results.0.clone();
+        self.runtime.0.spawn(async move {
+            let Some(store) = store_arc.lock().await.clone() else {
+                return;
+            };
+            let result = f(store).await.unwrap_or_else(|e| DbResult::Failed {
+                job,
+                error: e.to_string(),
+            });
+            let _ = tx.send(result);
+        });
+    }
+}
+
+/// Sends anything left in the write queue to `store`
+async fn replay_queue(queue: &WriteQueue, store: &dyn ScoreStore, tx: &UnboundedSender<DbResult>) {
+    if queue.is_empty().await {
+        return;
+    }
+    let result = match queue.replay(store).await {
+        Ok(n) => DbResult::Replayed(n),
+        Err(e) => DbResult::Failed {
+            job: "queue replay",
+            error: e.to_string(),
+        },
+    };
+    let _ = tx.send(result);
+}
+
+// System params shared by the phase and menu systems
+
+/// The deck and what a session draws from it
+#[derive(SystemParam)]
+struct Deck<'w> {
+    qs: Res<'w, Questions>,
+    cats: Res<'w, SessionCategories>,
+    settings: Res<'w, Settings>,
+}
+
+impl Deck<'_> {
+    fn next(&self, game: &Game) -> Option<usize> {
+        next_question(&self.qs, game, &self.cats, &self.settings)
+    }
+
+    fn advance(&self, game: &mut Game) -> Option<usize> {
+        advance_question(&self.qs, game, &self.cats, &self.settings)
+    }
+}
+
+/// The two cards and their labels
+#[derive(SystemParam)]
+struct Table<'w, 's> {
+    cards: Query<'w, 's, (&'static Card, &'static mut Transform, &'static mut Visibility), Without<CardLabel>>,
+    labels: Query<'w, 's, CardLabelParts, Without<Card>>,
+}
+
+type CardLabelParts = (
+    &'static CardLabel,
+    &'static mut Text2d,
+    &'static mut Transform,
+    &'static mut Visibility,
+);
+
+/// Filter for a text queried alongside a `Table`
+type OffTable<T> = (With<T>, Without<Card>, Without<CardLabel>);
+
+impl Table<'_, '_> {
+    /// Puts `q` on the cards and shows them full size in their places
+    fn deal(&mut self, q: &Q) {
+        for (card, mut t, mut vis) in self.cards.iter_mut() {
+            *vis = Visibility::Visible;
+            t.scale = Vec3::ONE;
+            t.translation.x = card.choice.card_x();
+        }
+        for (lbl, mut txt, mut t, mut vis) in self.labels.iter_mut() {
+            *vis = Visibility::Visible;
+            t.scale = Vec3::ONE;
+            t.translation.x = lbl.choice.card_x();
+            txt.0 = q.option(lbl.choice).to_string();
+        }
+    }
+
+    fn hide(&mut self) {
+        for (.., mut vis) in self.cards.iter_mut() {
+            *vis = Visibility::Hidden;
+        }
+        for (.., mut vis) in self.labels.iter_mut() {
+            *vis = Visibility::Hidden;
+        }
+    }
+}
+
+/// The texts around the cards that screens show, hide and rewrite
+#[derive(Clone, Copy)]
+enum HudText {
+    Title,
+    Timer,
+    Hurry,
+    Result,
+    Stats,
+    Traits,
+    /// The hint line at the bottom of the screen
+    Hint,
+}
+
+/// One `HudText`, kept clear of the cards, their labels and the pulsing GO/UH OH texts so
+/// systems can query those alongside a `Hud`
+type HudPart<'w, 's, T> = Query<
+    'w,
+    's,
+    (&'static mut Text2d, &'static mut Visibility),
+    (With<T>, Without<Card>, Without<CardLabel>, Without<GoText>, Without<UhOhText>),
+>;
+
+/// The `HudPart` of each `HudText`, in declaration order
+type HudParts<'w, 's> = (
+    HudPart<'w, 's, TitleText>,
+    HudPart<'w, 's, TimerDisplay>,
+    HudPart<'w, 's, HurryText>,
+    HudPart<'w, 's, ResultDisplay>,
+    HudPart<'w, 's, StatsDisplay>,
+    HudPart<'w, 's, TraitSummary>,
+    HudPart<'w, 's, ReplayInstruction>,
+);
+
+/// Every `HudText`. They sit in a `ParamSet` so they need no filters against each other.
+#[derive(SystemParam)]
+struct Hud<'w, 's> {
+    parts: ParamSet<'w, 's, HudParts<'w, 's>>,
+    /// For the hints that name keys
+    bindings: Res<'w, Bindings>,
+}
+
+impl Hud<'_, '_> {
+    fn each(&mut self, part: HudText, mut f: impl FnMut(&mut Text2d, &mut Visibility)) {
+        match part {
+            HudText::Title => self.parts.p0().iter_mut().for_each(|(mut t, mut v)| f(&mut t, &mut v)),
+            HudText::Timer => self.parts.p1().iter_mut().for_each(|(mut t, mut v)| f(&mut t, &mut v)),
+            HudText::Hurry => self.parts.p2().iter_mut().for_each(|(mut t, mut v)| f(&mut t, &mut v)),
+            HudText::Result => self.parts.p3().iter_mut().for_each(|(mut t, mut v)| f(&mut t, &mut v)),
+            HudText::Stats => self.parts.p4().iter_mut().for_each(|(mut t, mut v)| f(&mut t, &mut v)),
+            HudText::Traits => self.parts.p5().iter_mut().for_each(|(mut t, mut v)| f(&mut t, &mut v)),
+            HudText::Hint => self.parts.p6().iter_mut().for_each(|(mut t, mut v)| f(&mut t, &mut v)),
+        }
+    }
+
+    fn show(&mut self, part: HudText, shown: bool) {
+        let vis = if shown { Visibility::Visible } else { Visibility::Hidden };
+        self.each(part, |_, v| *v = vis);
+    }
+
+    /// Rewrites `part`, leaving it shown or hidden
+    fn write(&mut self, part: HudText, text: impl Into<String>) {
+        let text = text.into();
+        self.each(part, |t, _| t.0 = text.clone());
+    }
+
+    fn show_text(&mut self, part: HudText, text: impl Into<String>) {
+        let text = text.into();
+        self.each(part, |t, v| {
+            t.0 = text.clone();
+            *v = Visibility::Visible;
+        });
+    }
+
+    fn play_hint(&mut self, mode: GameMode) {
+        let hint = mode.play_hint(&self.bindings);
+        self.write(HudText::Hint, hint);
+    }
+}
+
+/// The mouse and the spot it points at
+#[derive(SystemParam)]
+struct Pointer<'w, 's> {
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    windows: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    cam: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+}
+
+impl Pointer<'_, '_> {
+    fn world_pos(&self) -> Option<Vec2> {
+        let win = self.windows.get_single().ok()?;
+        let cursor = win.cursor_position()?;
+        viewport_world_pos(&self.cam, cursor)
+    }
+
+    /// Where the left button went down this frame
+    fn click(&self) -> Option<Vec2> {
+        self.mouse.just_pressed(MouseButton::Left).then(|| self.world_pos()).flatten()
+    }
+}
+
+/// What every menu screen reads and writes
+#[derive(SystemParam)]
+struct MenuUi<'w, 's> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+    pointer: Pointer<'w, 's>,
+    /// Lets menus follow the mouse only once it moves, so it doesn't fight the arrow keys
+    moved: EventReader<'w, 's, CursorMoved>,
+    buttons: Query<'w, 's, (&'static MenuButton, &'static Transform)>,
+    hint: Query<'w, 's, &'static mut Text2d, With<ReplayInstruction>>,
+    /// Hints name the bound keys, and the controls screen rebinds them
+    bindings: ResMut<'w, Bindings>,
+    sounds: EventWriter<'w, PlaySoundEvent>,
+    exit: EventWriter<'w, AppExit>,
+}
+
+impl MenuUi<'_, '_> {
+    fn pad(&self, button: GamepadButton) -> bool {
+        self.gamepads.iter().any(|g| g.just_pressed(button))
+    }
+
+    /// The on-screen button clicked this frame
+    fn clicked_button(&self) -> Option<MenuAction> {
+        self.pointer.click().and_then(|world| clicked_button(world, &self.buttons))
+    }
+
+    fn mouse_moved(&mut self) -> bool {
+        self.moved.read().count() > 0
+    }
+
+    fn set_hint(&mut self, hint: impl Into<String>) {
+        let hint = hint.into();
+        for mut txt in self.hint.iter_mut() {
+            txt.0 = hint.clone();
+        }
+    }
+
+    fn play(&mut self, sound: SoundType) {
+        self.sounds.send(PlaySoundEvent(sound));
+    }
+}
+
+/// What players hear, see and feel when an answer lands
+#[derive(SystemParam)]
+struct Feedback<'w> {
+    sounds: EventWriter<'w, PlaySoundEvent>,
+    fireworks: EventWriter<'w, SpawnFireworksEvent>,
+    rumble: EventWriter<'w, RumbleEvent>,
+}
+
+/// What replacing the deck touches: the deck, the game pointing into it, and the cards
+/// showing its current question
+#[derive(SystemParam)]
+struct DeckSwap<'w, 's> {
+    cats: Res<'w, SessionCategories>,
+    settings: Res<'w, Settings>,
+    source: ResMut<'w, DeckSource>,
+    qs: ResMut<'w, Questions>,
+    game: ResMut<'w, Game>,
+    labels: Query<'w, 's, (&'static CardLabel, &'static mut Text2d), Without<TitleText>>,
+    title: Query<'w, 's, &'static mut Text2d, With<TitleText>>,
+}
+
+// Events for audio playback
+#[derive(Event)]
+struct PlaySoundEvent(SoundType);
+
+#[derive(Event)]
+struct SpawnFireworksEvent {
+    x: f32,
+    intensity: i32,
+}
+
+/// Shakes the gamepad of `player` (by connection order), or every gamepad when `None`
+#[derive(Event)]
+struct RumbleEvent {
+    player: Option<usize>,
+    strength: f32,
+    secs: f32,
+}
+
+impl RumbleEvent {
+    /// A pick: light when calm, harder the more the cards tremble
+    fn pick(player: Option<usize>, tremble: f32) -> Self {
+        Self {
+            player,
+            strength: (0.25 + tremble * 0.5).min(1.0),
+            secs: 0.15,
+        }
+    }
+
+    fn timeout() -> Self {
+        Self {
+            player: None,
+            strength: 0.8,
+            secs: 0.4,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum SoundType {
+    Hover,
+    Click,
+    Tick,
+    TickUrgent,
+    Whoosh,
+    Result,
+    Select,
+    Go,
+    CardIn,
+}
+
+/// Creates a rounded rectangle mesh for cards
+fn create_rounded_rect_mesh(width: f32, height: f32, radius: f32) -> Mesh {
+    use bevy::render::mesh::{Indices, PrimitiveTopology};
+    
+    let hw = width / 2.0;
+    let hh = height / 2.0;
+    let r = radius.min(hw).min(hh); // Clamp radius
+    let segments = 8; // Segments per corner
+    
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    
+    // Center vertex
+    positions.push([0.0, 0.0, 0.0]);
+    uvs.push([0.5, 0.5]);
+    
+    // Generate vertices around the rounded rectangle
+    let corners = [
+        (hw - r, hh - r, 0.0),              // Top-right
+        (-hw + r, hh - r, std::f32::consts::FRAC_PI_2),  // Top-left
+        (-hw + r, -hh + r, std::f32::consts::PI),        // Bottom-left
+        (hw - r, -hh + r, std::f32::consts::PI * 1.5),   // Bottom-right
+    ];
+    
+    for (cx, cy, start_angle) in corners {
+        for i in 0..=segments {
+            let angle = start_angle + (i as f32 / segments as f32) * std::f32::consts::FRAC_PI_2;
+            let x = cx + r * angle.cos();
+            let y = cy + r * angle.sin();
+            positions.push([x, y, 0.0]);
+            uvs.push([(x / width) + 0.5, (y / height) + 0.5]);
+        }
+    }
+    
+    // Generate triangle fan indices
+    let num_outer = positions.len() as u32 - 1;
+    for i in 1..=num_outer {
+        let next = if i == num_outer { 1 } else { i + 1 };
+        indices.extend_from_slice(&[0, i, next]);
+    }
+    
+    Mesh::new(PrimitiveTopology::TriangleList, default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+fn main() {
+    // Load env vars
+    let _ = dotenvy::dotenv();
+
+    // Create tokio runtime for async DB operations
+    let runtime = Runtime::new().expect("Failed to create Tokio runtime");
+    let (db_tx, db_rx) = mpsc::unbounded_channel();
+
+    App::new()
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "TWO PEOPLE - CHOOSE FAST!".into(),
                 mode: bevy::window::WindowMode::BorderlessFullscreen(MonitorSelection::Primary),
                 ..default()
-            }),
+            }),
+            ..default()
+        }))
+        .insert_resource(ClearColor(BG_COLOR))
+        .init_resource::<Game>()
+        .init_resource::<Questions>()
+        .init_resource::<DeckSource>()
+        .init_resource::<SessionCategories>()
+        .init_resource::<Settings>()
+        .init_resource::<PartyRoster>()
+        .init_resource::<MainMenuCursor>()
+        .init_resource::<TouchDrag>()
+        .init_resource::<ControlsMenu>()
+        .init_resource::<Actions>()
+        .insert_resource(Bindings::load())
+        .init_resource::<ActiveStore>()
+        .init_resource::<DbStats>()
+        .init_resource::<ConnectionState>()
+        .insert_resource(TokioRuntime(runtime))
+        .insert_resource(DbResultSender(db_tx))
+        .insert_resource(DbResultReceiver(db_rx))
+        .add_event::<QuestionsLoaded>()
+        .init_asset::<QuestionPack>()
+        .init_asset_loader::<QuestionPackLoader>()
+        .add_event::<PlaySoundEvent>()
+        .add_event::<SpawnFireworksEvent>()
+        .add_event::<RumbleEvent>()
+        .add_systems(Startup, (setup_emoji_fallback, setup, setup_audio, setup_db, setup_pack))
+        .add_systems(PreUpdate, (drag_cards, read_actions).chain().after(InputSystem))
+        .add_systems(
+            Update,
+            (
+                intro_tick,
+                timer_tick,
+                hover_cards,
+                click_cards,
+                picked_tick,
+                transition_tick,
+                show_results,
+                update_visuals,
+                animate_particles,
+                animate_bg_shapes,
+                animate_fireworks,
+                spawn_fireworks,
+                animate_pulse,
+                screen_shake,
+                handle_sound_events,
+                handle_replay,
+                uhoh_tick,
+                update_progress_text,
+                sync_hearts,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                drain_db_results,
+                update_connection_indicator,
+                couples_input,
+                guess_input,
+                reveal_tick,
+                sync_player_badges,
+                refresh_stats_display,
+                apply_loaded_questions,
+                apply_question_pack,
+                sync_category_items,
+                category_menu_input,
+                sync_settings_items,
+                settings_menu_input,
+                sync_menu_buttons,
+                sync_card_emojis,
+                party_names_input.before(category_menu_input),
+                party_handover,
+                sync_roster_text,
+                sync_party_columns,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                pause_input.before(settings_menu_input),
+                sync_pause_menu,
+                main_menu_input,
+                sync_main_menu,
+                stats_screen,
+                rumble_gamepads,
+                toggle_mute,
+                sync_music,
+                skip_question,
+                controls_input,
+                sync_controls_items,
+            ),
+        )
+        .run();
+}
+
+fn setup_audio(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    let sounds = GameSounds {
+        hover: asset_server.load("sounds/hover.ogg"),
+        click: asset_server.load("sounds/click.ogg"),
+        tick: asset_server.load("sounds/tick.ogg"),
+        tick_urgent: asset_server.load("sounds/tick_urgent.ogg"),
+        whoosh: asset_server.load("sounds/whoosh.ogg"),
+        result: asset_server.load("sounds/result.ogg"),
+        select: asset_server.load("sounds/select.ogg"),
+        go: asset_server.load("sounds/go.ogg"),
+        card_in: asset_server.load("sounds/card_in.ogg"),
+    };
+    cmd.insert_resource(sounds);
+
+    cmd.spawn((
+        AudioPlayer::new(asset_server.load("sounds/music.ogg")),
+        PlaybackSettings {
+            mode: PlaybackMode::Loop,
+            volume: Volume::new(1.0),
+            ..default()
+        },
+        BgMusic,
+    ));
+}
+
+fn setup_db(db: Db) {
+    let database_url = std::env::var("DATABASE_URL").ok();
+
+    if let Some(url) = database_url {
+        info!("Database URL found, connecting...");
+        db.runtime.0.spawn(manage_connection(
+            url,
+            db.store.store.clone(),
+            db.store.queue.clone(),
+            db.results.0.clone(),
+        ));
+    } else {
+        warn!("No DATABASE_URL found in environment, scores won't be saved");
+    }
+}
+
+/// Keeps the store connected for the life of the app: opens it, retrying with exponential
+/// backoff, then pings it and starts over if it stops answering
+async fn manage_connection(
+    url: String,
+    store_arc: Arc<Mutex<Option<Arc<dyn ScoreStore>>>>,
+    queue: Option<Arc<WriteQueue>>,
+    tx: UnboundedSender<DbResult>,
+) {
+    let mut backoff = RECONNECT_MIN;
+    let mut deck_loaded = false;
+
+    loop {
+        let _ = tx.send(DbResult::Connection(ConnectionState::Connecting));
+        let store = match store::open(&url).await {
+            Ok(store) => store,
+            Err(e) => {
+                warn!("Failed to open score store, retrying in {}s: {}", backoff.as_secs(), e);
+                let _ = tx.send(DbResult::Connection(ConnectionState::Failed {
+                    retry_secs: backoff.as_secs(),
+                }));
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(RECONNECT_MAX);
+                continue;
+            }
+        };
+        backoff = RECONNECT_MIN;
+
+        if !deck_loaded {
+            match store.questions().await {
+                Ok(deck) if !deck.is_empty() => {
+                    info!("Loaded {} questions from {}", deck.len(), store.name());
+                    let _ = tx.send(DbResult::Questions(deck));
+                    deck_loaded = true;
+                }
+                Ok(_) => deck_loaded = true,
+                Err(e) => warn!("Failed to load questions, using built-in deck: {}", e),
+            }
+        }
+
+        info!("Saving scores to {}", store.name());
+        *store_arc.lock().await = Some(store.clone());
+        let _ = tx.send(DbResult::Connection(ConnectionState::Connected(store.name())));
+        // Results from earlier runs, or from while we were offline
+        if let Some(queue) = &queue {
+            replay_queue(queue, store.as_ref(), &tx).await;
+        }
+
+        loop {
+            tokio::time::sleep(PING_INTERVAL).await;
+            let error = match tokio::time::timeout(PING_TIMEOUT, store.ping()).await {
+                Ok(Ok(())) => continue,
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => "timed out".to_string(),
+            };
+            warn!("Lost connection to {}: {}", store.name(), error);
+            break;
+        }
+
+        // Writes queue on disk until the next connection is up
+        *store_arc.lock().await = None;
+        let _ = tx.send(DbResult::Connection(ConnectionState::Disconnected));
+    }
+}
+
+/// Swaps in the database deck once the async load has finished
+fn apply_loaded_questions(
+    mut loaded: EventReader<QuestionsLoaded>,
+    pack: Option<Res<ActivePack>>,
+    mut deck: DeckSwap,
+) {
+    for QuestionsLoaded(questions) in loaded.read() {
+        if pack.is_some() {
+            info!("QUESTION_PACK is set, ignoring database deck");
+            continue;
+        }
+
+        deck.swap(questions.clone(), DeckSource::Database);
+    }
+}
+
+/// Makes the emoji fonts a fallback for every text, so emojis in labels and titles
+/// render too. They have to be in the font system before the first layout, which
+/// caches the fonts each text can fall back to.
+fn setup_emoji_fallback(mut fonts: ResMut<CosmicFontSystem>) {
+    let db = fonts.0.db_mut();
+    let mut system = fontdb::Database::new();
+    system.load_system_fonts();
+    let color = system.faces().find(|face| {
+        face.families.iter().any(|(name, _)| COLOR_EMOJI_FAMILIES.contains(&name.as_str()))
+            && system.with_face_data(face.id, renders_in_color) == Some(true)
+    });
+    match color {
+        Some(face) => {
+            info!("Drawing emoji with {}", face.families[0].0);
+            db.load_font_source(face.source.clone());
+        }
+        None => info!("No color emoji font installed, emoji will be monochrome"),
+    }
+    // Last in line, so only glyphs the color font lacks come from it
+    db.load_font_data(include_bytes!("../assets/fonts/NotoEmoji-Regular.ttf").to_vec());
+}
+
+/// Whether text rendering can draw this font's glyphs in color: COLRv0 layers or CBDT/sbix
+/// bitmaps. COLRv1 fonts, like newer Noto Color Emoji builds, render as blanks.
+fn renders_in_color(data: &[u8], index: u32) -> bool {
+    ttf_parser::Face::parse(data, index).is_ok_and(|face| {
+        let tables = face.tables();
+        tables.colr.is_some() || tables.cbdt.is_some() || tables.sbix.is_some()
+    })
+}
+
+fn setup_pack(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    if let Ok(name) = std::env::var("QUESTION_PACK") {
+        info!("Loading question pack: {}", name);
+        cmd.insert_resource(ActivePack(asset_server.load(format!("packs/{name}.pack.ron"))));
+    }
+}
+
+/// Replaces the deck when the active pack loads, and again whenever the file changes on disk
+fn apply_question_pack(
+    mut events: EventReader<AssetEvent<QuestionPack>>,
+    pack: Option<Res<ActivePack>>,
+    packs: Res<Assets<QuestionPack>>,
+    mut deck: DeckSwap,
+) {
+    let Some(pack) = pack else {
+        events.clear();
+        return;
+    };
+
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = *event else {
+            continue;
+        };
+        if id != pack.0.id() {
+            continue;
+        }
+        let Some(loaded) = packs.get(id) else {
+            continue;
+        };
+        if loaded.questions.is_empty() {
+            warn!("Question pack '{}' is empty, keeping current deck", loaded.name);
+            continue;
+        }
+
+        info!("Question pack '{}' ready: {} questions", loaded.name, loaded.questions.len());
+        deck.swap(loaded.questions.clone(), DeckSource::Pack);
+    }
+}
+
+impl DeckSwap<'_, '_> {
+    fn swap(&mut self, deck: Vec<Q>, source: DeckSource) {
+        *self.source = source;
+        let (qs, game, cats, settings) = (&mut *self.qs, &mut *self.game, &*self.cats, &*self.settings);
+        let current_id = qs.0.get(game.question).map(|q| q.id);
+        let used_titles = qs.titles(&game.used_questions);
+        let party_titles = qs.titles(&game.party_questions);
+        *qs = Questions::shuffled(deck);
+        // Questions already asked stay asked, wherever the shuffle put them
+        game.used_questions = qs.positions(&used_titles);
+        game.party_questions = qs.positions(&party_titles);
+
+        // Keep the current question if it survived, so a hot reload edits it in place
+        let keep = current_id
+            .and_then(|id| qs.0.iter().position(|q| q.id == id))
+            .filter(|&i| game.phase.takes_answers() || (cats.allows(&qs.0[i]) && settings.allows(&qs.0[i])));
+        let Some(question) = keep.or_else(|| next_question(qs, game, cats, settings)) else {
+            // Nothing left to ask. Mid-session that ends the session, and the restart finds the
+            // deck empty and goes back to the categories.
+            game.question = 0;
+            if game.phase.pausable() || game.phase == Phase::Paused {
+                game.paused_from = None;
+                game.restart_requested = true;
+            }
+            return;
+        };
+        game.question = question;
+        if !game.used_questions.contains(&question) {
+            game.used_questions.push(question);
+        }
+
+        // Keep the cards on screen in sync with the new deck
+        let q = &qs.0[game.question];
+        if game.phase == Phase::Intro || game.phase.takes_answers() {
+            for (lbl, mut txt) in self.labels.iter_mut() {
+                txt.0 = match lbl.choice {
+                    Choice::Left => q.left.clone(),
+                    Choice::Right => q.right.clone(),
+                };
+            }
+        }
+        if game.phase.takes_answers() {
+            for mut txt in self.title.iter_mut() {
+                txt.0 = q.title.clone();
+            }
+        }
+    }
+}
+
+impl ResponseRecord {
+    fn new(game: &mut Game, qs: &Questions, source: DeckSource, choice: Option<Choice>, reaction: Option<f32>) -> Self {
+        let q = &qs.0[game.question];
+        game.responses_recorded += 1;
+        Self {
+            session_id: game.session_id.clone(),
+            seq: game.responses_recorded,
+            player: None,
+            question_id: (source == DeckSource::Database).then_some(q.id),
+            question_text: q.title.clone(),
+            choice,
+            outcome: Outcome::Answered,
+            auto_picked: false,
+            reaction_ms: reaction.map(|t| (t * 1000.0) as i32),
+        }
+    }
+
+    /// A timeout, with the answer the timeout policy picked if it did
+    fn timed_out(game: &mut Game, qs: &Questions, source: DeckSource, auto: Option<Choice>) -> Self {
+        Self {
+            outcome: Outcome::TimedOut,
+            auto_picked: auto.is_some(),
+            ..Self::new(game, qs, source, auto, None)
+        }
+    }
+}
+
+fn record_session_start(db: &Db, session_id: String) {
+    db.write(ScoreWrite::SessionStart { session_id });
+}
+
+fn record_response(db: &Db, record: ResponseRecord) {
+    db.write(ScoreWrite::Response(record));
+}
+
+/// Hands finished database jobs to the ECS, the only place their results land
+fn drain_db_results(
+    mut inbox: ResMut<DbResultReceiver>,
+    mut db_stats: ResMut<DbStats>,
+    mut connection: ResMut<ConnectionState>,
+    mut loaded: EventWriter<QuestionsLoaded>,
+) {
+    while let Ok(result) = inbox.0.try_recv() {
+        match result {
+            DbResult::Questions(deck) => {
+                loaded.send(QuestionsLoaded(deck));
+            }
+            DbResult::Stats(stats) => *db_stats = stats,
+            DbResult::Saved(job) => debug!("Saved {}", job),
+            DbResult::Queued { job, error } => {
+                warn!("Couldn't save {} ({}), queued it in {}", job, error, WRITE_QUEUE_PATH)
+            }
+            DbResult::Rejected { job, error, moved_to } => {
+                warn!("Database refused {} ({}), set it aside in {}", job, error, moved_to.display())
+            }
+            DbResult::Replayed(n) if n > 0 => info!("Saved {} queued writes", n),
+            DbResult::Replayed(_) => {}
+            DbResult::Connection(state) => *connection = state,
+            DbResult::Failed { job, error } => warn!("Database {} failed: {}", job, error),
+        }
+    }
+}
+
+fn update_progress_text(
+    game: Res<Game>,
+    settings: Res<Settings>,
+    mut text: Query<(&mut Text2d, &mut Visibility), With<ProgressText>>,
+) {
+    let running = game.phase.takes_answers()
+        || matches!(game.phase, Phase::Picked | Phase::Reveal | Phase::Transition | Phase::UhOh);
+    for (mut txt, mut v) in text.iter_mut() {
+        if !running {
+            *v = Visibility::Hidden;
+            continue;
+        }
+        let label = match settings.questions_per_run() {
+            Some(n) => format!("{} / {}", game.asked, n),
+            None => game.asked.to_string(),
+        };
+        if txt.0 != label {
+            txt.0 = label;
+        }
+        *v = Visibility::Visible;
+    }
+}
+
+/// Survival mode: a row of hearts under the progress counter, dimmed as lives are lost
+fn sync_hearts(
+    mut cmd: Commands,
+    game: Res<Game>,
+    settings: Res<Settings>,
+    mut hearts: Query<(Entity, &Heart, &mut TextColor, &mut Visibility)>,
+) {
+    let rules = settings.rules();
+    let running = game.phase.takes_answers()
+        || matches!(game.phase, Phase::Picked | Phase::Reveal | Phase::Transition | Phase::UhOh);
+
+    if hearts.iter().count() != rules.lives as usize {
+        for (entity, ..) in hearts.iter() {
+            cmd.entity(entity).despawn();
+        }
+        for index in 0..rules.lives {
+            cmd.spawn((
+                Text2d::new("\u{2764}"),
+                TextFont {
+                    font_size: 28.0,
+                    ..default()
+                },
+                TextColor(CARD_LEFT),
+                Anchor::TopRight,
+                Transform::from_xyz(WINDOW_WIDTH / 2.0 - 16.0 - index as f32 * 34.0, WINDOW_HEIGHT / 2.0 - 50.0, 30.0),
+                Visibility::Hidden,
+                Heart { index },
+            ));
+        }
+        return;
+    }
+
+    for (_, heart, mut color, mut v) in hearts.iter_mut() {
+        *v = if running && rules.show_hearts { Visibility::Visible } else { Visibility::Hidden };
+        // Hearts are lost from the right, where index 0 sits
+        let alive = rules.lives - 1 - heart.index < game.lives;
+        color.0 = if alive { CARD_LEFT } else { Color::srgba(1.0, 1.0, 1.0, 0.2) };
+    }
+}
+
+fn update_connection_indicator(
+    connection: Res<ConnectionState>,
+    mut indicator: Query<(&mut Text2d, &mut TextColor), With<ConnectionIndicator>>,
+) {
+    if !connection.is_changed() {
+        return;
+    }
+    for (mut txt, mut color) in indicator.iter_mut() {
+        txt.0 = connection.label();
+        color.0 = connection.color();
+    }
+}
+
+fn setup(
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mats: ResMut<Assets<ColorMaterial>>,
+    qs: Res<Questions>,
+) {
+    // Camera
+    cmd.spawn(Camera2d);
+
+    // Pause backdrop, drawn over the cards but under the pause and settings menus
+    cmd.spawn((
+        Mesh2d(meshes.add(Rectangle::new(WINDOW_WIDTH * 3.0, WINDOW_HEIGHT * 3.0))),
+        MeshMaterial2d(mats.add(ColorMaterial::from(BG_COLOR.with_alpha(0.85)))),
+        Transform::from_xyz(0.0, 0.0, 40.0),
+        Visibility::Hidden,
+        PauseOverlay,
+    ));
+    cmd.spawn((
+        Text2d::new("PAUSED"),
+        TextFont {
+            font_size: 72.0,
+            ..default()
+        },
+        TextColor(TEXT_YELLOW),
+        Transform::from_xyz(0.0, 240.0, 45.0),
+        Visibility::Hidden,
+        PauseOverlay,
+    ));
+
+    // Title (question text - shown above cards)
+    cmd.spawn((
+        Text2d::new("TWO PEOPLE"),
+        TextFont {
+            font_size: 48.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Transform::from_xyz(0.0, 250.0, 10.0),
+        TitleText,
+        Pulse { speed: 3.0 },
+    ));
+
+    // "CHOOSE FAST!" subtitle
+    cmd.spawn((
+        Text2d::new("CHOOSE FAST!"),
+        TextFont {
+            font_size: 28.0,
+            ..default()
+        },
+        TextColor(TEXT_YELLOW),
+        Transform::from_xyz(0.0, 200.0, 10.0),
+        HurryText,
+    ));
+
+    // Timer (top of screen)
+    cmd.spawn((
+        Text2d::new("5"),
+        TextFont {
+            font_size: 56.0,
+            ..default()
+        },
+        TextColor(TIMER_NORMAL),
+        Transform::from_xyz(0.0, 320.0, 10.0),
+        Visibility::Hidden,
+        TimerDisplay,
+    ));
+
+    // Result
+    cmd.spawn((
+        Text2d::new(""),
+        TextFont {
+            font_size: 64.0,
+            ..default()
+        },
+        TextColor(RESULT_GREEN),
+        Transform::from_xyz(0.0, 0.0, 10.0),
+        Visibility::Hidden,
+        ResultDisplay,
+    ));
+
+    let q = &qs.0[0];
+    let card_mesh = meshes.add(create_rounded_rect_mesh(CARD_W, CARD_H, CARD_RADIUS));
+
+    // Left card
+    let lx = -CARD_GAP / 2.0;
+    cmd.spawn((
+        Mesh2d(card_mesh.clone()),
+        MeshMaterial2d(mats.add(ColorMaterial::from(CARD_LEFT))),
+        Transform::from_xyz(lx, -20.0, 0.0).with_scale(Vec3::ZERO),
+        Visibility::Hidden,
+        Card {
+            choice: Choice::Left,
+            base_y: -20.0,
+        },
+    ))
+    .with_child((
+        Text2d::new(q.emoji(Choice::Left)),
+        TextFont {
+            font_size: 80.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Transform::from_xyz(0.0, 105.0, 1.0),
+        CardEmoji {
+            choice: Choice::Left,
+        },
+    ));
+    cmd.spawn((
+        Text2d::new(q.left.to_string()),
+        TextFont {
+            font_size: 40.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextBounds::new_horizontal(CARD_W - 30.0),
+        Transform::from_xyz(lx, -20.0, 1.0).with_scale(Vec3::ZERO),
+        Visibility::Hidden,
+        CardLabel {
+            choice: Choice::Left,
+        },
+    ));
+
+    // Right card
+    let rx = CARD_GAP / 2.0;
+    cmd.spawn((
+        Mesh2d(card_mesh.clone()),
+        MeshMaterial2d(mats.add(ColorMaterial::from(CARD_RIGHT))),
+        Transform::from_xyz(rx, -20.0, 0.0).with_scale(Vec3::ZERO),
+        Visibility::Hidden,
+        Card {
+            choice: Choice::Right,
+            base_y: -20.0,
+        },
+    ))
+    .with_child((
+        Text2d::new(q.emoji(Choice::Right)),
+        TextFont {
+            font_size: 80.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Transform::from_xyz(0.0, 105.0, 1.0),
+        CardEmoji {
+            choice: Choice::Right,
+        },
+    ));
+    cmd.spawn((
+        Text2d::new(q.right.to_string()),
+        TextFont {
+            font_size: 40.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextBounds::new_horizontal(CARD_W - 30.0),
+        Transform::from_xyz(rx, -20.0, 1.0).with_scale(Vec3::ZERO),
+        Visibility::Hidden,
+        CardLabel {
+            choice: Choice::Right,
+        },
+    ));
+
+    let mut rng = rand::rng();
+    
+    for i in 0..15 {
+        let size = rng.random_range(200.0..700.0);
+        let x = rng.random_range(-900.0..900.0);
+        let y = rng.random_range(-500.0..500.0);
+        let a = rng.random_range(0.02..0.06);
+        let hue = (i as f32 / 15.0) * 360.0;
+        let c = Color::hsla(hue, 0.6, 0.5, a);
+        let sides = [3, 4, 5, 6, 8][rng.random_range(0..5)];
+        let mesh = meshes.add(RegularPolygon::new(size, sides));
+        cmd.spawn((
+            Mesh2d(mesh),
+            MeshMaterial2d(mats.add(ColorMaterial::from(c))),
+            Transform::from_xyz(x, y, -10.0),
+            BgShape {
+                spin_speed: rng.random_range(-0.15..0.15),
+                pulse_speed: rng.random_range(0.3..0.8),
+                phase: rng.random_range(0.0..std::f32::consts::TAU),
+            },
+        ));
+    }
+
+    for _ in 0..100 {
+        let x = rng.random_range(-WINDOW_WIDTH / 2.0..WINDOW_WIDTH / 2.0);
+        let y = rng.random_range(-WINDOW_HEIGHT / 2.0..WINDOW_HEIGHT / 2.0);
+        let s = rng.random_range(8.0..40.0);
+        let a = rng.random_range(0.03..0.12);
+        let hue = rng.random_range(0.0..360.0);
+        let c = Color::hsla(hue, 0.5, 0.5, a);
+        let mesh = meshes.add(Circle::new(s));
+        cmd.spawn((
+            Mesh2d(mesh),
+            MeshMaterial2d(mats.add(ColorMaterial::from(c))),
+            Transform::from_xyz(x, y, -5.0),
+            Particle {
+                vel: Vec2::new(
+                    rng.random_range(-15.0..15.0),
+                    rng.random_range(8.0..25.0),
+                ),
+                phase: rng.random_range(0.0..std::f32::consts::TAU),
+                spin: rng.random_range(-0.3..0.3),
+            },
+        ));
+    }
+
+    cmd.spawn((
+        Text2d::new(MAIN_MENU_HINT),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::srgba(1.0, 1.0, 1.0, 0.5)),
+        Transform::from_xyz(0.0, -320.0, 10.0),
+        ReplayInstruction,
+    ));
+
+    cmd.spawn((
+        Text2d::new(""),
+        TextFont {
+            font_size: 28.0,
+            ..default()
+        },
+        TextColor(Color::srgba(1.0, 1.0, 0.5, 0.9)),
+        Transform::from_xyz(0.0, -80.0, 10.0),
+        Visibility::Hidden,
+        StatsDisplay,
+    ));
+
+    cmd.spawn((
+        Text2d::new(""),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, -200.0, 10.0),
+        Visibility::Hidden,
+        TraitSummary,
+    ));
+
+    let connection = ConnectionState::default();
+    cmd.spawn((
+        Text2d::new(connection.label()),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(connection.color()),
+        Anchor::TopLeft,
+        Transform::from_xyz(-WINDOW_WIDTH / 2.0 + 16.0, WINDOW_HEIGHT / 2.0 - 12.0, 30.0),
+        ConnectionIndicator,
+    ));
+    cmd.spawn((
+        Text2d::new(""),
+        TextFont {
+            font_size: 28.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Anchor::TopRight,
+        Transform::from_xyz(WINDOW_WIDTH / 2.0 - 16.0, WINDOW_HEIGHT / 2.0 - 12.0, 30.0),
+        Visibility::Hidden,
+        ProgressText,
+    ));
+
+    for offset in [(6.0, -6.0, 22.0), (4.0, -4.0, 23.0), (2.0, -2.0, 24.0)] {
+        cmd.spawn((
+            Text2d::new("UH OH! TOO SLOW!"),
+            TextFont {
+                font_size: 100.0,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            Transform::from_xyz(offset.0, offset.1, offset.2),
+            Visibility::Hidden,
+            UhOhText,
+        ));
+    }
+    cmd.spawn((
+        Text2d::new("UH OH! TOO SLOW!"),
+        TextFont {
+            font_size: 100.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.9, 0.0)),
+        Transform::from_xyz(0.0, 0.0, 25.0),
+        Visibility::Hidden,
+        UhOhText,
+    ));
+
+    for player in 0..2 {
+        cmd.spawn((
+            Text2d::new(PLAYER_NAMES[player]),
+            TextFont {
+                font_size: 32.0,
+                ..default()
+            },
+            TextColor(PLAYER_COLORS[player]),
+            TextLayout::new_with_justify(JustifyText::Center),
+            Transform::from_xyz(0.0, 0.0, 12.0),
+            Visibility::Hidden,
+            PlayerBadge { player },
+        ));
+    }
+    cmd.spawn((
+        Text2d::new(""),
+        TextFont {
+            font_size: 64.0,
             ..default()
-        }))
-        .insert_resource(ClearColor(BG_COLOR))
-        .init_resource::<Game>()
-        .init_resource::<Questions>()
-        .init_resource::<DbPool>()
-        .init_resource::<DbStats>()
-        .insert_resource(TokioRuntime(runtime))
-        .add_event::<PlaySoundEvent>()
-        .add_event::<SpawnFireworksEvent>()
-        .add_systems(Startup, (setup, setup_audio, setup_db))
-        .add_systems(
-            Update,
-            (
-                intro_tick,
-                timer_tick,
-                hover_cards,
-                click_cards,
-                picked_tick,
-                transition_tick,
-                show_results,
-                update_visuals,
-                animate_particles,
-                animate_bg_shapes,
-                animate_fireworks,
-                spawn_fireworks,
-                animate_pulse,
-                screen_shake,
-                handle_sound_events,
-                handle_replay,
-                uhoh_tick,
-            ),
-        )
-        .run();
+        },
+        TextColor(RESULT_GREEN),
+        Transform::from_xyz(0.0, 200.0, 20.0),
+        Visibility::Hidden,
+        RevealText,
+    ));
+    cmd.spawn((
+        Text2d::new(""),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(TEXT_YELLOW),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, 10.0, 20.0),
+        Visibility::Hidden,
+        RosterText,
+    ));
+    cmd.spawn((
+        Text2d::new(""),
+        TextFont {
+            font_size: 34.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_xyz(0.0, 40.0, 10.0),
+        Visibility::Hidden,
+        StatsScreenText,
+    ));
+
+    // "GO!" text for transitions (hidden initially)
+    cmd.spawn((
+        Text2d::new("GO!"),
+        TextFont {
+            font_size: 120.0,
+            ..default()
+        },
+        TextColor(TEXT_YELLOW),
+        Transform::from_xyz(0.0, 0.0, 20.0),
+        Visibility::Hidden,
+        GoText,
+    ));
+}
+
+/// Card emojis always follow the question on screen, whichever system dealt it
+fn sync_card_emojis(
+    game: Res<Game>,
+    qs: Res<Questions>,
+    mut emojis: Query<(&CardEmoji, &mut Text2d)>,
+) {
+    let Some(q) = qs.0.get(game.question) else {
+        return;
+    };
+    for (em, mut txt) in emojis.iter_mut() {
+        let want = q.emoji(em.choice);
+        if txt.0 != want {
+            txt.0 = want.to_string();
+        }
+    }
+}
+
+fn handle_sound_events(
+    mut cmd: Commands,
+    mut events: EventReader<PlaySoundEvent>,
+    sounds: Option<Res<GameSounds>>,
+    settings: Res<Settings>,
+) {
+    let Some(sounds) = sounds else { return };
+
+    for event in events.read() {
+        if settings.muted {
+            continue;
+        }
+        let source = match event.0 {
+            SoundType::Hover => sounds.hover.clone(),
+            SoundType::Click => sounds.click.clone(),
+            SoundType::Tick => sounds.tick.clone(),
+            SoundType::TickUrgent => sounds.tick_urgent.clone(),
+            SoundType::Whoosh => sounds.whoosh.clone(),
+            SoundType::Result => sounds.result.clone(),
+            SoundType::Select => sounds.select.clone(),
+            SoundType::Go => sounds.go.clone(),
+            SoundType::CardIn => sounds.card_in.clone(),
+        };
+
+        // Spawn audio with PlaybackSettings
+        cmd.spawn((
+            AudioPlayer::new(source),
+            PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new(0.5),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Connected gamepads in connection order, so the first pad is player one
+fn player_pads<'a>(gamepads: &'a Query<(Entity, &Gamepad)>) -> Vec<(Entity, &'a Gamepad)> {
+    let mut pads: Vec<_> = gamepads.iter().collect();
+    pads.sort_by_key(|(entity, _)| *entity);
+    pads
+}
+
+fn rumble_gamepads(
+    mut events: EventReader<RumbleEvent>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+) {
+    let pads = player_pads(&gamepads);
+    for ev in events.read() {
+        let targets = match ev.player {
+            Some(player) => pads.get(player).into_iter().collect::<Vec<_>>(),
+            None => pads.iter().collect(),
+        };
+        for (gamepad, _) in targets {
+            requests.send(GamepadRumbleRequest::Add {
+                gamepad: *gamepad,
+                duration: Duration::from_secs_f32(ev.secs),
+                intensity: GamepadRumbleIntensity {
+                    strong_motor: ev.strength,
+                    weak_motor: ev.strength,
+                },
+            });
+        }
+    }
+}
+
+const MENU_ROW_H: f32 = 40.0;
+const MENU_TOP: f32 = 140.0;
+const MAIN_MENU_HINT: &str = "Click, or ARROWS / D-PAD to move and ENTER / A to choose";
+const CONTROLS_HINT: &str = "ENTER / A to add a binding, BACKSPACE / X to clear, ESC / B to go back";
+const CATEGORY_HINT: &str = "Click or press 1-9 to toggle categories, ESC to go back";
+const NO_QUESTIONS_HINT: &str = "No questions match, pick some more categories!";
+const SETTINGS_HINT: &str = "Click or press 1-9 to change, ESC to go back";
+const PAUSE_ROWS: [&str; 4] = ["RESUME", "RESTART", "SETTINGS", "QUIT"];
+const PARTY_NAMES_HINT: &str = "Type a name and press ENTER. BACKSPACE to fix, ESC to go back";
+
+fn pause_hint(bindings: &Bindings) -> String {
+    format!("Click or press 1-4, {} to resume", bindings.hint(Action::Pause))
+}
+
+const DIGITS: [KeyCode; 10] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Digit0,
+];
+
+fn menu_row_y(index: usize) -> f32 {
+    MENU_TOP - index as f32 * MENU_ROW_H
+}
+
+/// Row under a world position, for menus laid out with `menu_row_y`
+fn menu_row_at(world: Vec2, rows: usize) -> Option<usize> {
+    if world.x.abs() > 250.0 {
+        return None;
+    }
+    let row = ((MENU_TOP + MENU_ROW_H / 2.0 - world.y) / MENU_ROW_H).floor();
+    (row >= 0.0 && (row as usize) < rows).then_some(row as usize)
+}
+
+fn clicked_button(world: Vec2, buttons: &Query<(&MenuButton, &Transform)>) -> Option<MenuAction> {
+    buttons
+        .iter()
+        .find(|(b, t)| {
+            let d = (world - t.translation.truncate()).abs();
+            d.x < b.half_size.x && d.y < b.half_size.y
+        })
+        .map(|(b, _)| b.action)
+}
+
+/// Buttons along the bottom of each menu phase
+fn menu_buttons(phase: Phase) -> &'static [(MenuAction, &'static str, f32)] {
+    match phase {
+        Phase::Categories => &[(MenuAction::Start, "START!", -130.0), (MenuAction::Back, "BACK", 150.0)],
+        Phase::Settings | Phase::Stats | Phase::Controls => &[(MenuAction::Back, "BACK", 0.0)],
+        Phase::PartyNames => &[(MenuAction::Start, "START!", -130.0), (MenuAction::Back, "BACK", 150.0)],
+        _ => &[],
+    }
+}
+
+fn sync_menu_buttons(
+    mut cmd: Commands,
+    game: Res<Game>,
+    buttons: Query<(Entity, &MenuButton)>,
+) {
+    let wanted = menu_buttons(game.phase);
+    let up_to_date = buttons.iter().count() == wanted.len()
+        && buttons
+            .iter()
+            .all(|(_, b)| wanted.iter().any(|(action, ..)| *action == b.action));
+    if up_to_date {
+        return;
+    }
+
+    for (entity, _) in buttons.iter() {
+        cmd.entity(entity).despawn();
+    }
+    for &(action, label, x) in wanted {
+        cmd.spawn((
+            Text2d::new(label),
+            TextFont {
+                font_size: 40.0,
+                ..default()
+            },
+            TextColor(if action == MenuAction::Start { RESULT_GREEN } else { Color::WHITE }),
+            Transform::from_xyz(x, -260.0, 45.0),
+            MenuButton {
+                action,
+                half_size: Vec2::new(label.len() as f32 * 13.0, 28.0),
+            },
+        ));
+    }
+}
+
+/// Keeps the category menu rows in step with the deck, which can change while the menu is open
+fn sync_category_items(
+    mut cmd: Commands,
+    game: Res<Game>,
+    qs: Res<Questions>,
+    cats: Res<SessionCategories>,
+    settings: Res<Settings>,
+    mut items: Query<(Entity, &CategoryItem, &mut Text2d, &mut TextColor)>,
+) {
+    if game.phase != Phase::Categories {
+        for (entity, ..) in items.iter() {
+            cmd.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let deck = deck_categories(&qs, &settings);
+    let up_to_date = items.iter().count() == deck.len()
+        && items
+            .iter()
+            .all(|(_, item, ..)| deck.get(item.index).is_some_and(|(c, _)| *c == item.category));
+
+    if !up_to_date {
+        for (entity, ..) in items.iter() {
+            cmd.entity(entity).despawn();
+        }
+        for (index, (category, count)) in deck.iter().enumerate() {
+            cmd.spawn((
+                Text2d::new(format!("{}  {} ({})", index + 1, category.to_uppercase(), count)),
+                TextFont {
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(TEXT_YELLOW),
+                Transform::from_xyz(0.0, menu_row_y(index), 10.0),
+                CategoryItem {
+                    category: category.clone(),
+                    index,
+                },
+            ));
+        }
+    }
+
+    for (_, item, mut txt, mut col) in items.iter_mut() {
+        let on = !cats.disabled.contains(&item.category);
+        let count = deck.get(item.index).map_or(0, |(_, n)| *n);
+        txt.0 = format!(
+            "{}  {} {} ({})",
+            item.index + 1,
+            if on { "[x]" } else { "[ ]" },
+            item.category.to_uppercase(),
+            count
+        );
+        col.0 = if on { TEXT_YELLOW } else { Color::srgba(1.0, 1.0, 1.0, 0.3) };
+    }
+}
+
+fn category_menu_input(
+    mut game: ResMut<Game>,
+    qs: Res<Questions>,
+    mut cats: ResMut<SessionCategories>,
+    settings: Res<Settings>,
+    items: Query<&CategoryItem>,
+    mut ui: MenuUi,
+) {
+    if game.phase != Phase::Categories {
+        return;
+    }
+
+    let keyboard = &ui.keyboard;
+    let mut toggle: Option<usize> = DIGITS.iter().position(|k| keyboard.just_pressed(*k));
+    let mut action = if keyboard.just_pressed(KeyCode::Enter) || keyboard.just_pressed(KeyCode::Space) {
+        Some(MenuAction::Start)
+    } else if keyboard.just_pressed(KeyCode::Escape) || keyboard.just_pressed(KeyCode::Backspace) {
+        Some(MenuAction::Back)
+    } else {
+        None
+    };
+
+    if let Some(world) = ui.pointer.click() {
+        toggle = toggle.or(menu_row_at(world, items.iter().count()));
+        action = action.or(clicked_button(world, &ui.buttons));
+    }
+
+    if let Some(index) = toggle
+        && let Some(item) = items.iter().find(|item| item.index == index)
+    {
+        if !cats.disabled.remove(&item.category) {
+            cats.disabled.insert(item.category.clone());
+        }
+        ui.play(SoundType::Click);
+    }
+
+    match action {
+        Some(MenuAction::Start) => {
+            if !begin_session(&mut game, &qs, &cats, &settings) {
+                ui.set_hint(NO_QUESTIONS_HINT);
+                return;
+            }
+            let hint = session_hint(&game, &settings, &ui.bindings);
+            ui.set_hint(hint);
+            ui.play(SoundType::Go);
+        }
+        Some(MenuAction::Back) => {
+            game.phase = Phase::MainMenu;
+            ui.set_hint(MAIN_MENU_HINT);
+            ui.play(SoundType::Click);
+        }
+        None => {}
+    }
+}
+
+fn sync_settings_items(
+    mut cmd: Commands,
+    game: Res<Game>,
+    settings: Res<Settings>,
+    bindings: Res<Bindings>,
+    mut items: Query<(Entity, &SettingsItem, &mut Text2d)>,
+) {
+    if game.phase != Phase::Settings {
+        for (entity, ..) in items.iter() {
+            cmd.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let rows = settings.rows(&bindings);
+    if items.iter().count() != rows.len() {
+        for (entity, ..) in items.iter() {
+            cmd.entity(entity).despawn();
+        }
+        for (index, row) in rows.iter().enumerate() {
+            cmd.spawn((
+                Text2d::new(format!("{}  {}", index + 1, row)),
+                TextFont {
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(TEXT_YELLOW),
+                Transform::from_xyz(0.0, menu_row_y(index), 45.0),
+                SettingsItem { index },
+            ));
+        }
+        return;
+    }
+
+    for (_, item, mut txt) in items.iter_mut() {
+        txt.0 = format!("{}  {}", item.index + 1, rows[item.index]);
+    }
+}
+
+fn settings_menu_input(mut game: ResMut<Game>, mut settings: ResMut<Settings>, mut ui: MenuUi) {
+    if game.phase != Phase::Settings {
+        return;
+    }
+
+    let keyboard = &ui.keyboard;
+    let rows = settings.rows(&ui.bindings).len();
+    let mut change: Option<usize> = DIGITS.iter().position(|k| keyboard.just_pressed(*k)).filter(|&i| i < rows);
+    let mut back = keyboard.just_pressed(KeyCode::Escape) || keyboard.just_pressed(KeyCode::Backspace);
+
+    if let Some(world) = ui.pointer.click() {
+        change = change.or(menu_row_at(world, rows));
+        back |= clicked_button(world, &ui.buttons) == Some(MenuAction::Back);
+    }
+
+    // Switching modes mid-session would leave the session half in each
+    if change == Some(1) && game.paused_from.is_some() {
+        ui.set_hint("Restart or finish the session to change mode");
+        change = None;
+    }
+
+    if let Some(index) = change {
+        settings.change(index);
+        ui.play(SoundType::Click);
+    }
+
+    if back {
+        let paused = game.paused_from.is_some();
+        game.phase = if paused { Phase::Paused } else { Phase::MainMenu };
+        let hint = if paused { pause_hint(&ui.bindings) } else { MAIN_MENU_HINT.into() };
+        ui.set_hint(hint);
+        ui.play(SoundType::Click);
+    }
+}
+
+fn main_menu_input(
+    mut game: ResMut<Game>,
+    mut cursor: ResMut<MainMenuCursor>,
+    mut settings: ResMut<Settings>,
+    qs: Res<Questions>,
+    cats: Res<SessionCategories>,
+    db: Db,
+    mut ui: MenuUi,
+) {
+    let mouse_moved = ui.mouse_moved();
+    if game.phase != Phase::MainMenu {
+        return;
+    }
+
+    let keyboard = &ui.keyboard;
+    let up = keyboard.just_pressed(KeyCode::ArrowUp) || keyboard.just_pressed(KeyCode::KeyW) || ui.pad(GamepadButton::DPadUp);
+    let down = keyboard.just_pressed(KeyCode::ArrowDown) || keyboard.just_pressed(KeyCode::KeyS) || ui.pad(GamepadButton::DPadDown);
+    let left = keyboard.just_pressed(KeyCode::ArrowLeft) || ui.pad(GamepadButton::DPadLeft);
+    let right = keyboard.just_pressed(KeyCode::ArrowRight) || ui.pad(GamepadButton::DPadRight);
+    let mut chosen = (keyboard.just_pressed(KeyCode::Enter)
+        || keyboard.just_pressed(KeyCode::Space)
+        || ui.pad(GamepadButton::South))
+    .then_some(cursor.0);
+    if let Some(row) = DIGITS.iter().position(|k| keyboard.just_pressed(*k)).filter(|&i| i < MAIN_MENU_ROWS) {
+        chosen = Some(row);
+    }
+
+    if up {
+        cursor.0 = (cursor.0 + MAIN_MENU_ROWS - 1) % MAIN_MENU_ROWS;
+        ui.play(SoundType::Hover);
+    }
+    if down {
+        cursor.0 = (cursor.0 + 1) % MAIN_MENU_ROWS;
+        ui.play(SoundType::Hover);
+    }
+    let clicked = ui.pointer.mouse.just_pressed(MouseButton::Left);
+    if let Some(world) = ui.pointer.world_pos()
+        && let Some(row) = menu_row_at(world, MAIN_MENU_ROWS)
+    {
+        if mouse_moved && row != cursor.0 {
+            cursor.0 = row;
+            ui.play(SoundType::Hover);
+        }
+        if clicked {
+            chosen = Some(row);
+        }
+    }
+    // Left and right step the mode without leaving the row
+    if cursor.0 == 1 && (left || right) {
+        chosen = Some(1);
+    }
+
+    let Some(row) = chosen else {
+        return;
+    };
+    cursor.0 = row;
+    match row {
+        0 => {
+            if !begin_session(&mut game, &qs, &cats, &settings) {
+                game.phase = Phase::Categories;
+                ui.set_hint(NO_QUESTIONS_HINT);
+                return;
+            }
+            let hint = session_hint(&game, &settings, &ui.bindings);
+            ui.set_hint(hint);
+            ui.play(SoundType::Go);
+            return;
+        }
+        1 => settings.mode = settings.mode.next(),
+        2 => {
+            game.phase = Phase::Categories;
+            ui.set_hint(CATEGORY_HINT);
+        }
+        3 => {
+            game.phase = Phase::Settings;
+            ui.set_hint(SETTINGS_HINT);
+        }
+        4 => {
+            game.phase = Phase::Controls;
+            ui.set_hint(CONTROLS_HINT);
+        }
+        5 => {
+            game.phase = Phase::Stats;
+            ui.set_hint("ESC or B to go back");
+            db.read("stats", |store| async move {
+                let stats = store.stats().await?;
+                let top = store.leaderboard(3).await?;
+                Ok(DbResult::Stats(DbStats {
+                    loaded: true,
+                    total_players: stats.players,
+                    avg_left_pct: stats.avg_left_pct,
+                    top_runs: top.into_iter().map(|e| (e.answers, false)).collect(),
+                }))
+            });
+        }
+        _ => {
+            ui.exit.send(AppExit::Success);
+        }
+    }
+    ui.play(SoundType::Click);
 }
 
-fn setup_audio(mut cmd: Commands, asset_server: Res<AssetServer>) {
-    let sounds = GameSounds {
-        hover: asset_server.load("sounds/hover.ogg"),
-        click: asset_server.load("sounds/click.ogg"),
-        tick: asset_server.load("sounds/tick.ogg"),
-        tick_urgent: asset_server.load("sounds/tick_urgent.ogg"),
-        whoosh: asset_server.load("sounds/whoosh.ogg"),
-        result: asset_server.load("sounds/result.ogg"),
-        select: asset_server.load("sounds/select.ogg"),
-        go: asset_server.load("sounds/go.ogg"),
-        card_in: asset_server.load("sounds/card_in.ogg"),
-    };
-    cmd.insert_resource(sounds);
+fn sync_main_menu(
+    mut cmd: Commands,
+    game: Res<Game>,
+    cursor: Res<MainMenuCursor>,
+    qs: Res<Questions>,
+    cats: Res<SessionCategories>,
+    settings: Res<Settings>,
+    mut items: Query<(Entity, &MainMenuItem, &mut Text2d, &mut TextColor)>,
+) {
+    if game.phase != Phase::MainMenu {
+        for (entity, ..) in items.iter() {
+            cmd.entity(entity).despawn();
+        }
+        return;
+    }
 
-    cmd.spawn((
-        AudioPlayer::new(asset_server.load("sounds/music.ogg")),
-        PlaybackSettings {
-            mode: PlaybackMode::Loop,
-            volume: Volume::new(1.0),
-            ..default()
-        },
-        BgMusic,
-    ));
-}
+    let rows = main_menu_rows(&qs, &cats, &settings);
+    if items.is_empty() {
+        for (index, row) in rows.iter().enumerate() {
+            cmd.spawn((
+                Text2d::new(row.clone()),
+                TextFont {
+                    font_size: 34.0,
+                    ..default()
+                },
+                TextColor(TEXT_YELLOW),
+                Transform::from_xyz(0.0, menu_row_y(index), 10.0),
+                MainMenuItem { index },
+            ));
+        }
+        return;
+    }
 
-fn setup_db(db_pool: Res<DbPool>, runtime: Res<TokioRuntime>) {
-    let database_url = std::env::var("DATABASE_URL").ok();
-    let pool_arc = db_pool.0.clone();
+    for (_, item, mut txt, mut col) in items.iter_mut() {
+        let selected = item.index == cursor.0;
+        let label = if selected { format!("> {} <", rows[item.index]) } else { rows[item.index].clone() };
+        if txt.0 != label {
+            txt.0 = label;
+        }
+        col.0 = if selected { RESULT_GREEN } else { TEXT_YELLOW };
+    }
+}
 
-    if let Some(url) = database_url {
-        info!("Database URL found, connecting...");
+/// The STATS screen's body text, kept apart from the hint line below it
+type StatsScreenOnly = (With<StatsScreenText>, Without<ReplayInstruction>);
 
-        // Spawn async task to connect to database
-        runtime.0.spawn(async move {
-            match MySqlPoolOptions::new()
-                .max_connections(5)
-                .connect(&url)
-                .await
-            {
-                Ok(pool) => {
-                    // Create table if not exists
-                    let create_table = r#"
-                        CREATE TABLE IF NOT EXISTS game_scores (
-                            id INT AUTO_INCREMENT PRIMARY KEY,
-                            session_id VARCHAR(36) NOT NULL,
-                            score_left INT NOT NULL,
-                            score_right INT NOT NULL,
-                            result_type VARCHAR(50) NOT NULL,
-                            played_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-                        )
-                    "#;
-
-                    if let Err(e) = sqlx::query(create_table).execute(&pool).await {
-                        warn!("Failed to create table: {}", e);
-                    } else {
-                        info!("Database connected and table ready");
-                    }
+/// The STATS screen: what every saved session adds up to, and the way back to the menu
+fn stats_screen(
+    mut game: ResMut<Game>,
+    db_stats: Res<DbStats>,
+    connection: Res<ConnectionState>,
+    mut text: Query<(&mut Text2d, &mut Visibility), StatsScreenOnly>,
+    mut ui: MenuUi,
+) {
+    if game.phase != Phase::Stats {
+        for (_, mut v) in text.iter_mut() {
+            *v = Visibility::Hidden;
+        }
+        return;
+    }
 
-                    let mut lock = pool_arc.lock().await;
-                    *lock = Some(pool);
-                }
-                Err(e) => {
-                    warn!("Failed to connect to database: {}", e);
-                }
-            }
-        });
+    let body = if db_stats.loaded && db_stats.total_players > 0 {
+        let mut lines = vec![
+            format!("{} PLAYERS SO FAR", db_stats.total_players),
+            format!(
+                "{:.0}% CHAOTIC vs {:.0}% ADULT",
+                db_stats.avg_left_pct,
+                100.0 - db_stats.avg_left_pct
+            ),
+        ];
+        if !db_stats.top_runs.is_empty() {
+            let runs: Vec<String> = db_stats.top_runs.iter().map(|(answers, _)| answers.to_string()).collect();
+            lines.push(format!("LONGEST RUNS: {}", runs.join(" · ")));
+        }
+        lines.join("\n")
+    } else if matches!(*connection, ConnectionState::Connected(_)) {
+        "NO SCORES YET\nFinish a session to be the first!".into()
     } else {
-        warn!("No DATABASE_URL found in environment, scores won't be saved to database");
+        "NO STATS OFFLINE\nSet DATABASE_URL to keep scores (see the README)".into()
+    };
+    for (mut txt, mut v) in text.iter_mut() {
+        if txt.0 != body {
+            txt.0 = body.clone();
+        }
+        *v = Visibility::Visible;
+    }
+
+    let back = ui.keyboard.just_pressed(KeyCode::Escape)
+        || ui.keyboard.just_pressed(KeyCode::Backspace)
+        || ui.pad(GamepadButton::East)
+        || ui.clicked_button() == Some(MenuAction::Back);
+    if back {
+        game.phase = Phase::MainMenu;
+        ui.set_hint(MAIN_MENU_HINT);
+        ui.play(SoundType::Click);
     }
 }
 
-fn setup(
-    mut cmd: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut mats: ResMut<Assets<ColorMaterial>>,
+/// The MUTE action, anywhere but while typing names or rebinding
+fn toggle_mute(
+    actions: Res<Actions>,
+    game: Res<Game>,
+    menu: Res<ControlsMenu>,
+    mut settings: ResMut<Settings>,
+) {
+    let typing = game.phase == Phase::PartyNames || (game.phase == Phase::Controls && menu.listening.is_some());
+    if actions.just_pressed(Action::Mute) && !typing {
+        settings.muted = !settings.muted;
+        info!("Sound {}", if settings.muted { "muted" } else { "on" });
+    }
+}
+
+/// Pauses the background music while muted. Checked every frame since the sink only shows up
+/// once the track has loaded.
+fn sync_music(settings: Res<Settings>, music: Query<&AudioSink, With<BgMusic>>) {
+    for sink in music.iter() {
+        if sink.is_paused() != settings.muted {
+            sink.toggle();
+        }
+    }
+}
+
+/// The SKIP action: moves on without an answer. Unlike a timeout it doesn't cost a life.
+fn skip_question(
+    actions: Res<Actions>,
+    mut game: ResMut<Game>,
     qs: Res<Questions>,
+    source: Res<DeckSource>,
+    settings: Res<Settings>,
+    db: Db,
+    mut sound_events: EventWriter<PlaySoundEvent>,
 ) {
-    // Camera
-    cmd.spawn(Camera2d);
+    if game.phase != Phase::Playing || !settings.mode.clicks_cards() || !actions.just_pressed(Action::Skip) {
+        return;
+    }
+    let mut record = ResponseRecord::new(&mut game, &qs, *source, None, None);
+    record.outcome = Outcome::Skipped;
+    if settings.mode == GameMode::Party {
+        record.player = game.players.get(game.turn).map(|p| p.name.clone());
+    }
+    record_response(&db, record);
+    game.streak = 0;
+    game.phase = Phase::Transition;
+    game.wait = 0.3;
+    sound_events.send(PlaySoundEvent(SoundType::Whoosh));
+}
 
-    // Title (question text - shown above cards)
-    cmd.spawn((
-        Text2d::new("TWO PEOPLE"),
-        TextFont {
-            font_size: 48.0,
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        Transform::from_xyz(0.0, 250.0, 10.0),
-        TitleText,
-        Pulse { speed: 3.0 },
-    ));
+fn controls_input(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut menu: ResMut<ControlsMenu>,
+    mut ui: MenuUi,
+) {
+    if game.phase != Phase::Controls {
+        menu.listening = None;
+        return;
+    }
 
-    // "CHOOSE FAST!" subtitle
-    cmd.spawn((
-        Text2d::new("CHOOSE FAST!"),
-        TextFont {
-            font_size: 28.0,
-            ..default()
-        },
-        TextColor(TEXT_YELLOW),
-        Transform::from_xyz(0.0, 200.0, 10.0),
-        HurryText,
-    ));
+    if let Some(left) = menu.listening {
+        // Any key, mouse button or gamepad button is added to the highlighted action
+        let pressed = ui
+            .keyboard
+            .get_just_pressed()
+            .next()
+            .map(|k| Binding::Key(*k))
+            .or_else(|| ui.pointer.mouse.get_just_pressed().next().map(|b| Binding::Mouse(*b)))
+            .or_else(|| ui.gamepads.iter().find_map(|g| g.get_just_pressed().next()).map(|b| Binding::Pad(*b)));
+        if let Some(binding) = pressed {
+            let action = Action::ALL[menu.cursor];
+            let taken = ui.bindings.add(action, binding);
+            ui.bindings.save();
+            menu.listening = None;
+            let hint = if taken.is_empty() {
+                CONTROLS_HINT.to_string()
+            } else {
+                let from: Vec<&str> = taken.iter().map(|a| a.label()).collect();
+                format!("{} moved to {} from {}", binding.label(), action.label(), from.join(", "))
+            };
+            ui.set_hint(hint);
+            ui.play(SoundType::Select);
+        } else {
+            menu.listening = Some(left - time.delta_secs()).filter(|&t| t > 0.0);
+        }
+        return;
+    }
 
-    // Timer (top of screen)
-    cmd.spawn((
-        Text2d::new("5"),
-        TextFont {
-            font_size: 56.0,
-            ..default()
-        },
-        TextColor(TIMER_NORMAL),
-        Transform::from_xyz(0.0, 320.0, 10.0),
-        Visibility::Hidden,
-        TimerDisplay,
-    ));
+    let keyboard = &ui.keyboard;
+    let up = keyboard.just_pressed(KeyCode::ArrowUp) || ui.pad(GamepadButton::DPadUp);
+    let down = keyboard.just_pressed(KeyCode::ArrowDown) || ui.pad(GamepadButton::DPadDown);
+    let mut chosen = keyboard.just_pressed(KeyCode::Enter) || ui.pad(GamepadButton::South);
+    let clear = keyboard.just_pressed(KeyCode::Backspace) || keyboard.just_pressed(KeyCode::Delete) || ui.pad(GamepadButton::West);
+    let mut back = keyboard.just_pressed(KeyCode::Escape) || ui.pad(GamepadButton::East);
+
+    let cursor = menu.cursor;
+    if up {
+        menu.move_to((cursor + CONTROLS_ROWS - 1) % CONTROLS_ROWS);
+        ui.play(SoundType::Hover);
+    }
+    if down {
+        menu.move_to((cursor + 1) % CONTROLS_ROWS);
+        ui.play(SoundType::Hover);
+    }
 
-    // Result
-    cmd.spawn((
-        Text2d::new(""),
-        TextFont {
-            font_size: 64.0,
-            ..default()
-        },
-        TextColor(RESULT_GREEN),
-        Transform::from_xyz(0.0, 0.0, 10.0),
-        Visibility::Hidden,
-        ResultDisplay,
-    ));
+    if let Some(world) = ui.pointer.click() {
+        if let Some(row) = menu_row_at(world, CONTROLS_VISIBLE.min(CONTROLS_ROWS)) {
+            let row = menu.scroll + row;
+            menu.move_to(row);
+            chosen = true;
+        }
+        back |= clicked_button(world, &ui.buttons) == Some(MenuAction::Back);
+    }
 
-    let q = &qs.0[0];
-    let card_mesh = meshes.add(create_rounded_rect_mesh(CARD_W, CARD_H, 25.0));
+    match Action::ALL.get(menu.cursor) {
+        Some(_) if chosen => {
+            menu.listening = Some(LISTEN_TIME);
+            ui.play(SoundType::Click);
+        }
+        Some(&action) if clear => {
+            ui.bindings.clear(action);
+            ui.bindings.save();
+            ui.play(SoundType::Click);
+        }
+        None if chosen => {
+            *ui.bindings = Bindings::default();
+            ui.bindings.save();
+            ui.play(SoundType::Click);
+        }
+        _ => {}
+    }
 
-    // Left card
-    let lx = -CARD_GAP / 2.0;
-    cmd.spawn((
-        Mesh2d(card_mesh.clone()),
-        MeshMaterial2d(mats.add(ColorMaterial::from(CARD_LEFT))),
-        Transform::from_xyz(lx, -20.0, 0.0).with_scale(Vec3::ZERO),
-        Visibility::Hidden,
-        Card {
-            choice: Choice::Left,
-            base_y: -20.0,
-        },
-    ));
-    cmd.spawn((
-        Text2d::new(q.left.to_string()),
-        TextFont {
-            font_size: 40.0,
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        Transform::from_xyz(lx, -20.0, 1.0).with_scale(Vec3::ZERO),
-        Visibility::Hidden,
-        CardLabel {
-            choice: Choice::Left,
-        },
-    ));
+    if back {
+        game.phase = Phase::MainMenu;
+        ui.set_hint(MAIN_MENU_HINT);
+        ui.play(SoundType::Click);
+    }
+}
 
-    // Right card
-    let rx = CARD_GAP / 2.0;
-    cmd.spawn((
-        Mesh2d(card_mesh.clone()),
-        MeshMaterial2d(mats.add(ColorMaterial::from(CARD_RIGHT))),
-        Transform::from_xyz(rx, -20.0, 0.0).with_scale(Vec3::ZERO),
-        Visibility::Hidden,
-        Card {
-            choice: Choice::Right,
-            base_y: -20.0,
-        },
-    ));
-    cmd.spawn((
-        Text2d::new(q.right.to_string()),
-        TextFont {
-            font_size: 40.0,
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        Transform::from_xyz(rx, -20.0, 1.0).with_scale(Vec3::ZERO),
-        Visibility::Hidden,
-        CardLabel {
-            choice: Choice::Right,
-        },
-    ));
+fn sync_controls_items(
+    mut cmd: Commands,
+    game: Res<Game>,
+    menu: Res<ControlsMenu>,
+    bindings: Res<Bindings>,
+    mut items: Query<(Entity, &ControlsItem, &mut Text2d, &mut TextColor, &mut Transform, &mut Visibility)>,
+) {
+    if game.phase != Phase::Controls {
+        for (entity, ..) in items.iter() {
+            cmd.entity(entity).despawn();
+        }
+        return;
+    }
 
-    let mut rng = rand::rng();
-    
-    for i in 0..15 {
-        let size = rng.random_range(200.0..700.0);
-        let x = rng.random_range(-900.0..900.0);
-        let y = rng.random_range(-500.0..500.0);
-        let a = rng.random_range(0.02..0.06);
-        let hue = (i as f32 / 15.0) * 360.0;
-        let c = Color::hsla(hue, 0.6, 0.5, a);
-        let sides = [3, 4, 5, 6, 8][rng.random_range(0..5)];
-        let mesh = meshes.add(RegularPolygon::new(size, sides));
-        cmd.spawn((
-            Mesh2d(mesh),
-            MeshMaterial2d(mats.add(ColorMaterial::from(c))),
-            Transform::from_xyz(x, y, -10.0),
-            BgShape {
-                spin_speed: rng.random_range(-0.15..0.15),
-                pulse_speed: rng.random_range(0.3..0.8),
-                phase: rng.random_range(0.0..std::f32::consts::TAU),
-            },
-        ));
+    if items.is_empty() {
+        for index in 0..CONTROLS_ROWS {
+            cmd.spawn((
+                Text2d::new(""),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(TEXT_YELLOW),
+                Transform::from_xyz(0.0, menu_row_y(index), 10.0),
+                Visibility::Hidden,
+                ControlsItem { index },
+            ));
+        }
+        return;
+    }
+
+    for (_, item, mut txt, mut col, mut t, mut vis) in items.iter_mut() {
+        if !(menu.scroll..menu.scroll + CONTROLS_VISIBLE).contains(&item.index) {
+            *vis = Visibility::Hidden;
+            continue;
+        }
+        *vis = Visibility::Visible;
+        t.translation.y = menu_row_y(item.index - menu.scroll);
+
+        let selected = item.index == menu.cursor;
+        let listening = menu.listening.filter(|_| selected);
+        let row = match (Action::ALL.get(item.index), listening) {
+            (Some(&action), Some(left)) => format!("{}: press a key or button ({})", action.label(), left.ceil()),
+            (Some(&action), None) => {
+                let bound: Vec<String> = bindings.get(action).iter().map(|b| b.label()).collect();
+                let bound = if bound.is_empty() { "-".to_string() } else { bound.join(", ") };
+                format!("{}: {}", action.label(), bound)
+            }
+            (None, _) => "RESET TO DEFAULTS".to_string(),
+        };
+        let label = if selected { format!("> {} <", row) } else { row };
+        if txt.0 != label {
+            txt.0 = label;
+        }
+        col.0 = if selected { RESULT_GREEN } else { TEXT_YELLOW };
+    }
+}
+
+/// ESC or losing window focus pauses mid-session. Systems only act in their own phase, so
+/// timers, waits and card animations all hold still until the game resumes.
+fn pause_input(
+    mut focus_events: EventReader<WindowFocused>,
+    actions: Res<Actions>,
+    mut game: ResMut<Game>,
+    settings: Res<Settings>,
+    mut ui: MenuUi,
+) {
+    let lost_focus = focus_events.read().any(|ev| !ev.focused);
+    let pause = actions.just_pressed(Action::Pause);
+
+    if game.phase.pausable() {
+        if pause || lost_focus {
+            game.paused_from = Some(game.phase);
+            game.phase = Phase::Paused;
+            let hint = pause_hint(&ui.bindings);
+            ui.set_hint(hint);
+            ui.play(SoundType::Click);
+        }
+        return;
+    }
+    if game.phase != Phase::Paused {
+        return;
     }
 
-    for _ in 0..100 {
-        let x = rng.random_range(-WINDOW_WIDTH / 2.0..WINDOW_WIDTH / 2.0);
-        let y = rng.random_range(-WINDOW_HEIGHT / 2.0..WINDOW_HEIGHT / 2.0);
-        let s = rng.random_range(8.0..40.0);
-        let a = rng.random_range(0.03..0.12);
-        let hue = rng.random_range(0.0..360.0);
-        let c = Color::hsla(hue, 0.5, 0.5, a);
-        let mesh = meshes.add(Circle::new(s));
-        cmd.spawn((
-            Mesh2d(mesh),
-            MeshMaterial2d(mats.add(ColorMaterial::from(c))),
-            Transform::from_xyz(x, y, -5.0),
-            Particle {
-                vel: Vec2::new(
-                    rng.random_range(-15.0..15.0),
-                    rng.random_range(8.0..25.0),
-                ),
-                phase: rng.random_range(0.0..std::f32::consts::TAU),
-                spin: rng.random_range(-0.3..0.3),
-            },
-        ));
+    let mut choice = DIGITS.iter().position(|k| ui.keyboard.just_pressed(*k)).filter(|&i| i < PAUSE_ROWS.len());
+    if pause {
+        choice = Some(0);
+    } else if actions.just_pressed(Action::Restart) {
+        choice = Some(1);
+    }
+    if let Some(world) = ui.pointer.click() {
+        choice = choice.or(menu_row_at(world, PAUSE_ROWS.len()));
     }
+    let Some(choice) = choice else {
+        return;
+    };
 
-    cmd.spawn((
-        Text2d::new("Click a card to choose!"),
-        TextFont {
-            font_size: 24.0,
-            ..default()
-        },
-        TextColor(Color::srgba(1.0, 1.0, 1.0, 0.5)),
-        Transform::from_xyz(0.0, -320.0, 10.0),
-        ReplayInstruction,
-    ));
+    ui.play(SoundType::Click);
+    match choice {
+        0 => {
+            game.phase = game.paused_from.take().unwrap_or(Phase::Playing);
+            let hint = settings.mode.play_hint(&ui.bindings);
+            ui.set_hint(hint);
+        }
+        1 => {
+            game.paused_from = None;
+            game.restart_requested = true;
+        }
+        2 => {
+            game.phase = Phase::Settings;
+            ui.set_hint(SETTINGS_HINT);
+        }
+        _ => {
+            ui.exit.send(AppExit::Success);
+        }
+    }
+}
 
-    cmd.spawn((
-        Text2d::new(""),
-        TextFont {
-            font_size: 28.0,
-            ..default()
-        },
-        TextColor(Color::srgba(1.0, 1.0, 0.5, 0.9)),
-        Transform::from_xyz(0.0, -80.0, 10.0),
-        Visibility::Hidden,
-        StatsDisplay,
-    ));
+fn sync_pause_menu(
+    mut cmd: Commands,
+    game: Res<Game>,
+    mut overlay: Query<&mut Visibility, With<PauseOverlay>>,
+    items: Query<Entity, With<PauseItem>>,
+) {
+    let overlaid = game.phase == Phase::Paused || game.paused_from.is_some();
+    for mut v in overlay.iter_mut() {
+        *v = if overlaid { Visibility::Visible } else { Visibility::Hidden };
+    }
 
-    for offset in [(6.0, -6.0, 22.0), (4.0, -4.0, 23.0), (2.0, -2.0, 24.0)] {
+    if game.phase != Phase::Paused {
+        for entity in items.iter() {
+            cmd.entity(entity).despawn();
+        }
+        return;
+    }
+    if !items.is_empty() {
+        return;
+    }
+    for (index, row) in PAUSE_ROWS.iter().enumerate() {
         cmd.spawn((
-            Text2d::new("UH OH! TOO SLOW!"),
+            Text2d::new(format!("{}  {}", index + 1, row)),
             TextFont {
-                font_size: 100.0,
+                font_size: 36.0,
                 ..default()
             },
-            TextColor(Color::BLACK),
-            Transform::from_xyz(offset.0, offset.1, offset.2),
-            Visibility::Hidden,
-            UhOhText,
+            TextColor(if index == 0 { RESULT_GREEN } else { TEXT_YELLOW }),
+            Transform::from_xyz(0.0, menu_row_y(index), 45.0),
+            PauseItem,
         ));
     }
-    cmd.spawn((
-        Text2d::new("UH OH! TOO SLOW!"),
-        TextFont {
-            font_size: 100.0,
-            ..default()
-        },
-        TextColor(Color::srgb(1.0, 0.9, 0.0)),
-        Transform::from_xyz(0.0, 0.0, 25.0),
-        Visibility::Hidden,
-        UhOhText,
-    ));
+}
 
-    // "GO!" text for transitions (hidden initially)
-    cmd.spawn((
-        Text2d::new("GO!"),
-        TextFont {
-            font_size: 120.0,
-            ..default()
-        },
-        TextColor(TEXT_YELLOW),
-        Transform::from_xyz(0.0, 0.0, 20.0),
-        Visibility::Hidden,
-        GoText,
-    ));
+/// Party mode name entry. Reads key events even in other phases so the ENTER that opened
+/// this screen isn't taken as "start" on the next frame.
+fn party_names_input(
+    mut key_events: EventReader<KeyboardInput>,
+    mut game: ResMut<Game>,
+    mut roster: ResMut<PartyRoster>,
+    deck: Deck,
+    mut ui: MenuUi,
+) {
+    let presses: Vec<Key> = key_events
+        .read()
+        .filter(|ev| ev.state == ButtonState::Pressed)
+        .map(|ev| ev.logical_key.clone())
+        .collect();
+    if game.phase != Phase::PartyNames {
+        return;
+    }
+
+    let mut action = None;
+    for key in presses {
+        match key {
+            Key::Character(text) => {
+                for c in text.chars().filter(|c| c.is_alphanumeric() || *c == ' ') {
+                    if roster.typing.chars().count() < PARTY_NAME_LEN && !(c == ' ' && roster.typing.is_empty()) {
+                        roster.typing.extend(c.to_uppercase());
+                    }
+                }
+            }
+            Key::Space if !roster.typing.is_empty() && roster.typing.chars().count() < PARTY_NAME_LEN => {
+                roster.typing.push(' ');
+            }
+            Key::Backspace => {
+                if roster.typing.is_empty() {
+                    roster.names.pop();
+                } else {
+                    roster.typing.pop();
+                }
+            }
+            Key::Enter if roster.typing.trim().is_empty() => action = Some(MenuAction::Start),
+            Key::Enter => {
+                if roster.names.len() < PARTY_MAX_PLAYERS {
+                    let name = roster.typing.trim().to_string();
+                    roster.names.push(name);
+                    ui.play(SoundType::Click);
+                }
+                roster.typing.clear();
+            }
+            Key::Escape => action = Some(MenuAction::Back),
+            _ => {}
+        }
+    }
+
+    action = action.or(ui.clicked_button());
+
+    match action {
+        Some(MenuAction::Start) if roster.ready() => {
+            game.players = deck.settings.mode.players(&roster);
+            game.reset_for_session(&deck.settings);
+            let Some(first) = deck.next(&game) else {
+                game.phase = Phase::Categories;
+                ui.set_hint(NO_QUESTIONS_HINT);
+                return;
+            };
+            game.question = first;
+            game.used_questions.push(first);
+            game.party_questions.push(first);
+            game.phase = Phase::Handover;
+            ui.play(SoundType::Go);
+        }
+        Some(MenuAction::Start) => {
+            ui.set_hint(format!("Add at least {} players!", PARTY_MIN_PLAYERS));
+        }
+        Some(MenuAction::Back) => {
+            game.phase = Phase::MainMenu;
+            ui.set_hint(MAIN_MENU_HINT);
+            ui.play(SoundType::Click);
+        }
+        _ => {}
+    }
 }
 
-fn handle_sound_events(
-    mut cmd: Commands,
-    mut events: EventReader<PlaySoundEvent>,
-    sounds: Option<Res<GameSounds>>,
+/// Party mode: clears the table between turns until the next player clicks or presses ENTER
+fn party_handover(
+    actions: Res<Actions>,
+    mut game: ResMut<Game>,
+    settings: Res<Settings>,
+    mut table: Table,
+    mut hud: Hud,
+    mut sound_events: EventWriter<PlaySoundEvent>,
 ) {
-    let Some(sounds) = sounds else { return };
+    if game.phase != Phase::Handover {
+        return;
+    }
 
-    for event in events.read() {
-        let source = match event.0 {
-            SoundType::Hover => sounds.hover.clone(),
-            SoundType::Click => sounds.click.clone(),
-            SoundType::Tick => sounds.tick.clone(),
-            SoundType::TickUrgent => sounds.tick_urgent.clone(),
-            SoundType::Whoosh => sounds.whoosh.clone(),
-            SoundType::Result => sounds.result.clone(),
-            SoundType::Select => sounds.select.clone(),
-            SoundType::Go => sounds.go.clone(),
-            SoundType::CardIn => sounds.card_in.clone(),
-        };
+    table.hide();
+    for part in [HudText::Title, HudText::Timer, HudText::Hurry] {
+        hud.show(part, false);
+    }
 
-        // Spawn audio with PlaybackSettings
+    if !actions.just_pressed(Action::Confirm) {
+        return;
+    }
+
+    let name = game.players.get(game.turn).map_or(String::new(), |p| p.name.clone());
+    hud.show_text(HudText::Title, format!("{}'S TURN!", name));
+    hud.play_hint(settings.mode);
+    game.phase = Phase::Intro;
+    game.wait = 1.2;
+    game.lives = settings.rules().lives;
+    game.streak = 0;
+    game.tremble = 0.0;
+    sound_events.send(PlaySoundEvent(SoundType::Go));
+}
+
+
+/// Party mode: the names typed so far, then who gets the laptop next
+fn sync_roster_text(
+    game: Res<Game>,
+    roster: Res<PartyRoster>,
+    bindings: Res<Bindings>,
+    time: Res<Time>,
+    mut text: Query<(&mut Text2d, &mut Visibility), With<RosterText>>,
+) {
+    let shown = match game.phase {
+        Phase::PartyNames => {
+            let mut lines = vec![format!("WHO'S PLAYING? ({}-{})", PARTY_MIN_PLAYERS, PARTY_MAX_PLAYERS)];
+            lines.extend(roster.names.iter().enumerate().map(|(i, n)| format!("{}  {}", i + 1, n)));
+            if roster.names.len() < PARTY_MAX_PLAYERS {
+                let cursor = if (time.elapsed_secs() * 2.0) as i32 % 2 == 0 { "_" } else { " " };
+                lines.push(format!("{}  {}{}", roster.names.len() + 1, roster.typing, cursor));
+            }
+            Some(lines.join("\n"))
+        }
+        Phase::Handover => game
+            .players
+            .get(game.turn)
+            .map(|p| format!("PASS TO {}!\n\nClick or press {} when ready", p.name, bindings.hint(Action::Confirm))),
+        _ => None,
+    };
+
+    for (mut txt, mut v) in text.iter_mut() {
+        match &shown {
+            Some(s) => {
+                if txt.0 != *s {
+                    txt.0 = s.clone();
+                }
+                *v = Visibility::Visible;
+            }
+            None => *v = Visibility::Hidden,
+        }
+    }
+}
+
+/// Party mode: every player's archetype side by side on the results screen
+fn sync_party_columns(
+    mut cmd: Commands,
+    game: Res<Game>,
+    settings: Res<Settings>,
+    columns: Query<Entity, With<PartyColumn>>,
+) {
+    let wanted = game.phase == Phase::Results && settings.mode == GameMode::Party;
+    if !wanted {
+        for entity in columns.iter() {
+            cmd.entity(entity).despawn();
+        }
+        return;
+    }
+    if !columns.is_empty() {
+        return;
+    }
+
+    let width = (WINDOW_WIDTH - 80.0) / game.players.len().max(1) as f32;
+    for (i, player) in game.players.iter().enumerate() {
+        let (title, _) = archetype(player.left, player.right);
+        let x = -WINDOW_WIDTH / 2.0 + 40.0 + width * (i as f32 + 0.5);
         cmd.spawn((
-            AudioPlayer::new(source),
-            PlaybackSettings {
-                mode: PlaybackMode::Despawn,
-                volume: Volume::new(0.5),
+            Text2d::new(format!(
+                "{}\n{}\n{} - {}{}",
+                player.name,
+                title.replace(' ', "\n"),
+                player.left,
+                player.right,
+                if player.auto_picks > 0 { format!("\n({} auto)", player.auto_picks) } else { String::new() }
+            )),
+            TextFont {
+                font_size: 22.0,
                 ..default()
             },
+            TextColor(if player.left > player.right { CARD_LEFT } else if player.right > player.left { CARD_RIGHT } else { TEXT_YELLOW }),
+            TextLayout::new_with_justify(JustifyText::Center),
+            Transform::from_xyz(x, 150.0, 10.0),
+            PartyColumn,
         ));
     }
 }
 
+/// World position of a point in window coordinates, such as a touch
+fn viewport_world_pos(cam: &Query<(&Camera, &GlobalTransform)>, pos: Vec2) -> Option<Vec2> {
+    let (camera, cam_t) = cam.get_single().ok()?;
+    camera.viewport_to_world_2d(cam_t, pos).ok()
+}
+
+/// Whether `world` lands on a card's rounded rect, following its wobble, spin and scale
+fn over_card(world: Vec2, card: &GlobalTransform) -> bool {
+    let local = card.affine().inverse().transform_point3(world.extend(0.0)).truncate();
+    // Signed distance to the rounded rect, negative inside
+    let inner = Vec2::new(CARD_W, CARD_H) / 2.0 - Vec2::splat(CARD_RADIUS);
+    let q = local.abs() - inner;
+    q.max(Vec2::ZERO).length() + q.max_element().min(0.0) - CARD_RADIUS <= 0.0
+}
+
+/// Card under `world`, if any. Where the cards overlap, the one whose centre is nearer wins.
+fn card_at<'a>(world: Vec2, cards: impl Iterator<Item = (&'a Card, &'a GlobalTransform)>) -> Option<Choice> {
+    cards
+        .filter(|(_, gt)| over_card(world, gt))
+        .min_by(|(_, a), (_, b)| {
+            let da = a.translation().truncate().distance_squared(world);
+            let db = b.translation().truncate().distance_squared(world);
+            da.total_cmp(&db)
+        })
+        .map(|(card, _)| card.choice)
+}
+
 fn intro_tick(
     time: Res<Time>,
     mut game: ResMut<Game>,
-    mut title: Query<&mut Visibility, With<TitleText>>,
-    mut hurry: Query<&mut Visibility, (With<HurryText>, Without<TitleText>)>,
-    mut cards: Query<
-        (&mut Visibility, &mut Transform),
-        (With<Card>, Without<TitleText>, Without<HurryText>),
-    >,
-    mut labels: Query<
-        (&mut Visibility, &mut Transform),
-        (
-            With<CardLabel>,
-            Without<Card>,
-            Without<TitleText>,
-            Without<HurryText>,
-        ),
-    >,
-    mut timer_vis: Query<
-        &mut Visibility,
-        (
-            With<TimerDisplay>,
-            Without<TitleText>,
-            Without<HurryText>,
-            Without<Card>,
-            Without<CardLabel>,
-        ),
-    >,
+    deck: Deck,
+    mut table: Table,
+    mut hud: Hud,
+    db: Db,
     mut sound_events: EventWriter<PlaySoundEvent>,
 ) {
     if game.phase != Phase::Intro {
@@ -717,42 +3753,39 @@ fn intro_tick(
 
     game.wait -= time.delta_secs();
     if game.wait <= 0.0 {
-        game.phase = Phase::Playing;
+        if game.turn == 0 {
+            record_session_start(&db, game.session_id.clone());
+        }
+        game.phase = deck.settings.mode.question_phase();
         game.timer = QUESTION_TIME;
         game.last_tick = 5;
 
         // Play whoosh sound when cards appear
         sound_events.send(PlaySoundEvent(SoundType::Whoosh));
 
-        // Hide intro
-        for mut v in title.iter_mut() {
-            *v = Visibility::Hidden;
-        }
-        for mut v in hurry.iter_mut() {
-            *v = Visibility::Hidden;
-        }
+        // Hide intro, the title comes back with the first question
+        let q = &deck.qs.0[game.question];
+        hud.write(HudText::Title, q.title.clone());
+        hud.show(HudText::Title, false);
+        hud.show(HudText::Hurry, false);
 
         // Show cards + timer
-        for (mut v, mut t) in cards.iter_mut() {
-            *v = Visibility::Visible;
-            t.scale = Vec3::ONE;
-        }
-        for (mut v, mut t) in labels.iter_mut() {
-            *v = Visibility::Visible;
-            t.scale = Vec3::ONE;
-        }
-        for mut v in timer_vis.iter_mut() {
-            *v = Visibility::Visible;
-        }
+        table.deal(q);
+        hud.show(HudText::Timer, true);
     }
 }
 
+
 fn timer_tick(
     time: Res<Time>,
     mut game: ResMut<Game>,
-    mut sound_events: EventWriter<PlaySoundEvent>,
+    qs: Res<Questions>,
+    source: Res<DeckSource>,
+    settings: Res<Settings>,
+    db: Db,
+    mut feedback: Feedback,
 ) {
-    if game.phase != Phase::Playing {
+    if !game.phase.takes_answers() {
         return;
     }
     game.timer -= time.delta_secs();
@@ -761,68 +3794,113 @@ fn timer_tick(
     if current_sec < game.last_tick && current_sec >= 0 {
         game.last_tick = current_sec;
         if current_sec <= 2 {
-            sound_events.send(PlaySoundEvent(SoundType::TickUrgent));
+            feedback.sounds.send(PlaySoundEvent(SoundType::TickUrgent));
         } else {
-            sound_events.send(PlaySoundEvent(SoundType::Tick));
+            feedback.sounds.send(PlaySoundEvent(SoundType::Tick));
         }
     }
 
-    if game.timer <= 0.0 {
-        game.timeouts += 1;
+    // A couples round with one answer in, or a late prediction, is revealed by
+    // `couples_input` / `guess_input` instead
+    let partly_answered = settings.mode == GameMode::Couples && game.couples.picks.iter().any(Option::is_some);
+    if game.timer <= 0.0 && !partly_answered && game.phase != Phase::Predict {
+        // Still a timeout for the rules, even when the game answers for the player
+        let auto = if settings.mode.clicks_cards() {
+            settings.on_timeout.pick(game.hovered_card)
+        } else {
+            None
+        };
+        game.lives -= 1;
         game.streak = 0;
-        
-        if game.timeouts >= 3 {
-            game.phase = Phase::Results;
-            sound_events.send(PlaySoundEvent(SoundType::Result));
+        feedback.rumble.send(RumbleEvent::timeout());
+        if let Some(choice) = auto {
+            let turn = game.turn;
+            game.record_answer(turn, choice);
+            if let Some(p) = game.players.get_mut(turn) {
+                p.auto_picks += 1;
+            }
+        }
+        let players: Vec<Option<String>> = match settings.mode {
+            GameMode::Solo => vec![None],
+            // Neither partner answered: a timeout each, as when only one of them did
+            GameMode::Couples => PLAYER_NAMES.iter().map(|name| Some(name.to_string())).collect(),
+            GameMode::Guess => vec![Some(PLAYER_NAMES[game.guess.answerer].into())],
+            GameMode::Party => vec![game.players.get(game.turn).map(|p| p.name.clone())],
+        };
+        for player in players {
+            let mut record = ResponseRecord::timed_out(&mut game, &qs, *source, auto);
+            record.player = player;
+            record_response(&db, record);
+        }
+        if settings.mode == GameMode::Guess {
+            game.guess.swap_roles();
+        }
+
+        if game.lives <= 0 {
+            game.phase = end_turn(&mut game, &settings);
+            feedback.sounds.send(PlaySoundEvent(SoundType::Result));
+        } else if let Some(choice) = auto {
+            game.picked = Some(choice);
+            game.phase = Phase::Picked;
+            game.wait = 0.6;
+            feedback.sounds.send(PlaySoundEvent(SoundType::Select));
         } else {
             game.phase = Phase::UhOh;
             game.wait = 1.2;
-            sound_events.send(PlaySoundEvent(SoundType::Whoosh));
+            feedback.sounds.send(PlaySoundEvent(SoundType::Whoosh));
         }
     }
 }
 
+/// Turns this frame's input into `Actions`. Clicking or tapping a card counts as choosing it,
+/// whatever the bindings say.
+fn read_actions(
+    bindings: Res<Bindings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    pointer: Pointer,
+    cards: Query<(&Card, &GlobalTransform)>,
+    mut drag: ResMut<TouchDrag>,
+    mut actions: ResMut<Actions>,
+) {
+    actions.pointed = pointer.world_pos().and_then(|world| card_at(world, cards.iter()));
+    actions.local = bindings.read_local(&keyboard, &pointer.mouse, actions.pointed);
+    actions.pads = player_pads(&gamepads).into_iter().map(|(_, pad)| bindings.read_pad(pad)).collect();
+
+    if let Some(choice) = drag.picked.take() {
+        actions.local.press(Action::choose(choice));
+    }
+}
+
 fn hover_cards(
-    windows: Query<&Window, With<PrimaryWindow>>,
-    cam: Query<(&Camera, &GlobalTransform)>,
-    mut cards: Query<(&Card, &mut Transform, &GlobalTransform)>,
-    mut labels: Query<(&CardLabel, &mut Transform), Without<Card>>,
+    actions: Res<Actions>,
+    drag: Res<TouchDrag>,
+    mut table: Table,
     mut game: ResMut<Game>,
+    settings: Res<Settings>,
     time: Res<Time>,
     mut sound_events: EventWriter<PlaySoundEvent>,
 ) {
-    if game.phase != Phase::Playing {
+    if !game.phase.takes_answers() {
         return;
     }
-
-    let Ok(win) = windows.get_single() else {
-        return;
-    };
-    let Ok((camera, cam_t)) = cam.get_single() else {
-        return;
-    };
-    let Some(cursor) = win.cursor_position() else {
-        return;
-    };
-    let Some(world) = camera.viewport_to_world_2d(cam_t, cursor).ok() else {
-        return;
+    let pressure_start = settings.length.pressure_start() as f32;
+
+    // A held choose binding wins over wherever the mouse happens to be
+    let key_hover = if settings.mode.clicks_cards() { actions.held_choice() } else { None };
+    let new_hover = if drag.touch.is_some() {
+        drag.card
+    } else if key_hover.is_some() {
+        key_hover
+    } else {
+        actions.pointed
     };
 
-    let mut new_hover: Option<Choice> = None;
-
-    for (card, mut t, gt) in cards.iter_mut() {
-        let pos = gt.translation().truncate();
-        let hovered = world.x >= pos.x - CARD_W / 2.0
-            && world.x <= pos.x + CARD_W / 2.0
-            && world.y >= pos.y - CARD_H / 2.0
-            && world.y <= pos.y + CARD_H / 2.0;
-
-        if hovered {
-            new_hover = Some(card.choice);
-        }
+    for (card, mut t, _) in table.cards.iter_mut() {
+        let hovered = new_hover == Some(card.choice);
 
         let t_secs = time.elapsed_secs();
-        let after_warmup = (game.answers_count as f32 - 20.0).max(0.0);
+        let after_warmup = (game.answers_count as f32 - pressure_start).max(0.0);
         let progress = after_warmup / 10.0;
         let chaos = (progress + game.tremble * 2.0).min(3.0);
         let bounce_intensity = 1.0 + chaos * 4.0;
@@ -839,7 +3917,7 @@ fn hover_cards(
         let tremble_x = (t_secs * 50.0 + phase_offset).sin() * 8.0 * game.tremble;
         let tremble_y = (t_secs * 55.0).cos() * 6.0 * game.tremble;
         
-        let urgency_factor = ((game.answers_count as f32 - 20.0).max(0.0) / 15.0).min(1.5);
+        let urgency_factor = ((game.answers_count as f32 - pressure_start).max(0.0) / 15.0).min(1.5);
         let time_elapsed = 1.0 - (game.timer / QUESTION_TIME);
         let urgency_shake = time_elapsed * time_elapsed * urgency_factor * 12.0;
         let uh_oh_x = (t_secs * 45.0 + phase_offset).sin() * urgency_shake;
@@ -849,10 +3927,16 @@ fn hover_cards(
         let panic_spin = (t_secs * 40.0).sin() * 0.1 * game.tremble;
         let uh_oh_spin = (t_secs * 38.0).sin() * 0.04 * time_elapsed * urgency_factor;
         
-        let base_x = if card.choice == Choice::Left { -CARD_GAP / 2.0 } else { CARD_GAP / 2.0 };
+        let base_x = card.choice.card_x();
         t.translation.y = card.base_y + bob + tremble_y + uh_oh_y;
         t.translation.x = base_x + side_bob + tremble_x + uh_oh_x;
         t.rotation = Quat::from_rotation_z(spin + panic_spin + uh_oh_spin);
+
+        // Follows the finger while dragged, tilting like a card being thrown
+        if drag.card == Some(card.choice) {
+            t.translation += drag.offset.extend(0.0);
+            t.rotation *= Quat::from_rotation_z(-drag.offset.x * 0.0015);
+        }
     }
 
     // Detect hover change and play sound
@@ -864,8 +3948,8 @@ fn hover_cards(
     }
 
     // Sync label positions with cards
-    for (label, mut lt) in labels.iter_mut() {
-        for (card, ct, _) in cards.iter() {
+    for (label, _, mut lt, _) in table.labels.iter_mut() {
+        for (card, ct, _) in table.cards.iter() {
             if card.choice == label.choice {
                 lt.translation = ct.translation + Vec3::Z;
                 lt.scale = ct.scale;
@@ -874,32 +3958,87 @@ fn hover_cards(
     }
 }
 
-fn click_cards(
-    mouse: Res<ButtonInput<MouseButton>>,
-    windows: Query<&Window, With<PrimaryWindow>>,
+/// Touchscreens: tap a card to pick it, or drag it and fling it away. The card follows the
+/// finger and eases back into place when the fling is too weak.
+fn drag_cards(
+    time: Res<Time>,
+    touches: Res<Touches>,
     cam: Query<(&Camera, &GlobalTransform)>,
-    mut game: ResMut<Game>,
-    mut sound_events: EventWriter<PlaySoundEvent>,
-    mut firework_events: EventWriter<SpawnFireworksEvent>,
+    cards: Query<(&Card, &GlobalTransform)>,
+    game: Res<Game>,
+    settings: Res<Settings>,
+    mut drag: ResMut<TouchDrag>,
 ) {
-    if game.phase != Phase::Playing || !mouse.just_pressed(MouseButton::Left) {
+    if game.phase != Phase::Playing || !settings.mode.clicks_cards() {
+        *drag = TouchDrag::default();
         return;
     }
+    let dt = time.delta_secs();
 
-    let Ok(win) = windows.get_single() else {
+    let Some(id) = drag.touch else {
+        drag.offset = drag.offset.lerp(Vec2::ZERO, (12.0 * dt).min(1.0));
+        if drag.offset.length() < 1.0 {
+            drag.card = None;
+        }
+        // Grab whichever card the finger lands on
+        for touch in touches.iter_just_pressed() {
+            let Some(world) = viewport_world_pos(&cam, touch.position()) else {
+                continue;
+            };
+            let Some(card) = card_at(world, cards.iter()) else {
+                continue;
+            };
+            *drag = TouchDrag {
+                touch: Some(touch.id()),
+                card: Some(card),
+                start: world,
+                ..default()
+            };
+            break;
+        }
         return;
     };
-    let Ok((camera, cam_t)) = cam.get_single() else {
+
+    if let Some(touch) = touches.get_pressed(id) {
+        if let Some(world) = viewport_world_pos(&cam, touch.position()) {
+            let offset = world - drag.start;
+            if dt > 0.0 {
+                let velocity = (offset - drag.offset) / dt;
+                drag.velocity = drag.velocity.lerp(velocity, 0.5);
+            }
+            drag.offset = offset;
+        }
         return;
-    };
-    let Some(cursor) = win.cursor_position() else {
+    }
+
+    // Lifted (or cancelled): a tap or a strong fling picks, anything else snaps back
+    let released = touches.iter_just_released().any(|t| t.id() == id);
+    let tapped = drag.offset.length() < TAP_SLOP;
+    let flung = drag.offset.length() > FLING_DISTANCE || drag.velocity.length() > FLING_SPEED;
+    if released && (tapped || flung) {
+        drag.picked = drag.card;
+    }
+    drag.touch = None;
+    drag.velocity = Vec2::ZERO;
+}
+
+fn click_cards(
+    actions: Res<Actions>,
+    mut game: ResMut<Game>,
+    qs: Res<Questions>,
+    source: Res<DeckSource>,
+    settings: Res<Settings>,
+    db: Db,
+    mut feedback: Feedback,
+) {
+    if game.phase != Phase::Playing || !settings.mode.clicks_cards() {
         return;
-    };
-    let Some(world) = camera.viewport_to_world_2d(cam_t, cursor).ok() else {
+    }
+
+    // Keys, buttons, touches and clicks all end up here, so they share timing, tremble and fireworks
+    let Some(choice) = actions.choice() else {
         return;
     };
-
-    let choice = if world.x < 0.0 { Choice::Left } else { Choice::Right };
     
     let reaction_time = QUESTION_TIME - game.timer;
     game.total_reaction_time += reaction_time;
@@ -916,18 +4055,27 @@ fn click_cards(
     let avg_reaction = game.total_reaction_time / game.answers_count as f32;
     let speed_bonus = ((QUESTION_TIME - avg_reaction) / QUESTION_TIME * 10.0) as i32;
     
-    sound_events.send(PlaySoundEvent(SoundType::Select));
+    feedback.sounds.send(PlaySoundEvent(SoundType::Select));
     game.picked = Some(choice);
-    game.timeouts = 0;
     game.streak += 1;
+    game.register_answer(&settings.rules());
     
     let intensity = game.streak + speed_bonus.max(0) + (game.tremble * 5.0) as i32;
-    firework_events.send(SpawnFireworksEvent { x: 0.0, intensity });
-    
-    match choice {
-        Choice::Left => game.score_l += 1,
-        Choice::Right => game.score_r += 1,
-    };
+    feedback.fireworks.send(SpawnFireworksEvent { x: 0.0, intensity });
+    feedback.rumble.send(RumbleEvent::pick(None, game.tremble));
+    
+    let turn = game.turn;
+    game.record_answer(turn, choice);
+    if settings.mode == GameMode::Solo
+        && let Some(q) = qs.0.get(game.question)
+    {
+        game.record_trait(q, choice);
+    }
+    let mut record = ResponseRecord::new(&mut game, &qs, *source, Some(choice), Some(reaction_time));
+    if settings.mode == GameMode::Party {
+        record.player = Some(game.players[turn].name.clone());
+    }
+    record_response(&db, record);
     game.phase = Phase::Picked;
     game.wait = 0.6;
 }
@@ -935,17 +4083,10 @@ fn click_cards(
 fn picked_tick(
     time: Res<Time>,
     mut game: ResMut<Game>,
+    settings: Res<Settings>,
     qs: Res<Questions>,
-    mut cards: Query<(&Card, &mut Transform), Without<CardLabel>>,
-    mut labels: Query<(&CardLabel, &mut Transform), Without<Card>>,
-    mut title: Query<&mut Visibility, With<TitleText>>,
-    mut timer_vis: Query<
-        &mut Visibility,
-        (
-            With<TimerDisplay>,
-            Without<TitleText>,
-        ),
-    >,
+    mut table: Table,
+    mut hud: Hud,
     mut sound_events: EventWriter<PlaySoundEvent>,
 ) {
     if game.phase != Phase::Picked {
@@ -954,7 +4095,7 @@ fn picked_tick(
 
     let lerp_speed = 12.0 * time.delta_secs();
 
-    for (card, mut t) in cards.iter_mut() {
+    for (card, mut t, _) in table.cards.iter_mut() {
         if Some(card.choice) == game.picked {
             t.translation.x = t.translation.x * (1.0 - lerp_speed) + 0.0 * lerp_speed;
             t.scale = t.scale.lerp(Vec3::splat(1.3), lerp_speed);
@@ -965,8 +4106,8 @@ fn picked_tick(
         }
     }
 
-    for (label, mut lt) in labels.iter_mut() {
-        for (card, ct) in cards.iter() {
+    for (label, _, mut lt, _) in table.labels.iter_mut() {
+        for (card, ct, _) in table.cards.iter() {
             if card.choice == label.choice {
                 lt.translation.x = ct.translation.x;
                 lt.translation.y = ct.translation.y;
@@ -975,18 +4116,14 @@ fn picked_tick(
         }
     }
 
-    for mut v in title.iter_mut() {
-        *v = Visibility::Hidden;
-    }
-    for mut v in timer_vis.iter_mut() {
-        *v = Visibility::Hidden;
-    }
+    hud.show(HudText::Title, false);
+    hud.show(HudText::Timer, false);
 
     game.wait -= time.delta_secs();
     if game.wait <= 0.0 {
         game.phase = Phase::Transition;
-        let warmup = (20 - game.answers_count).max(0) as f32 / 20.0;
-        let speed_factor = if game.answers_count < 20 {
+        let pressure_start = settings.length.pressure_start();
+        let speed_factor = if game.answers_count < pressure_start {
             0.5
         } else if game.last_reaction < 1.0 {
             0.15
@@ -998,15 +4135,291 @@ fn picked_tick(
     }
 }
 
+/// Couples mode: partners lock in with their own keys. The round is revealed once both have
+/// answered, or when time runs out with at least one answer in.
+fn couples_input(
+    actions: Res<Actions>,
+    settings: Res<Settings>,
+    mut game: ResMut<Game>,
+    qs: Res<Questions>,
+    source: Res<DeckSource>,
+    db: Db,
+    mut feedback: Feedback,
+) {
+    if game.phase != Phase::Playing || settings.mode != GameMode::Couples {
+        return;
+    }
+
+    // The keyboard is split between the partners by the P1 and P2 actions. The first
+    // gamepad plays for P1, the second for P2.
+    let reaction_time = QUESTION_TIME - game.timer;
+    for (player, name) in PLAYER_NAMES.iter().enumerate() {
+        if game.couples.picks[player].is_some() {
+            continue;
+        }
+        let Some(choice) = actions.player_choice(player) else {
+            continue;
+        };
+        feedback.rumble.send(RumbleEvent::pick(Some(player), game.tremble));
+        game.couples.picks[player] = Some(choice);
+        game.record_answer(player, choice);
+        let mut record = ResponseRecord::new(&mut game, &qs, *source, Some(choice), Some(reaction_time));
+        record.player = Some(name.to_string());
+        record_response(&db, record);
+        feedback.sounds.send(PlaySoundEvent(SoundType::Select));
+    }
+
+    let picks = game.couples.picks;
+    let all_in = picks.iter().all(Option::is_some);
+    let any_in = picks.iter().any(Option::is_some);
+    let out_of_time = any_in && game.timer <= 0.0;
+    if !(all_in || out_of_time) {
+        return;
+    }
+
+    for (player, pick) in picks.iter().enumerate() {
+        if pick.is_none() {
+            let mut record = ResponseRecord::timed_out(&mut game, &qs, *source, None);
+            record.player = Some(PLAYER_NAMES[player].into());
+            record_response(&db, record);
+        }
+    }
+
+    game.total_reaction_time += reaction_time;
+    game.answers_count += 1;
+    game.last_reaction = reaction_time;
+
+    match picks {
+        [Some(a), Some(b)] if a == b => {
+            game.couples.answered += 1;
+            game.couples.matches += 1;
+            game.streak += 1;
+            feedback.fireworks.send(SpawnFireworksEvent {
+                x: 0.0,
+                intensity: game.streak,
+            });
+        }
+        [Some(a), Some(b)] => {
+            game.couples.answered += 1;
+            game.streak = 0;
+            if let Some(q) = qs.0.get(game.question) {
+                let label = |c: Choice| match c {
+                    Choice::Left => q.left.replace('\n', " "),
+                    Choice::Right => q.right.replace('\n', " "),
+                };
+                let disagreement = (q.title.clone(), label(a), label(b));
+                game.couples.disagreements.push(disagreement);
+            }
+        }
+        _ => game.streak = 0,
+    }
+    game.register_answer(&settings.rules());
+
+    game.phase = Phase::Reveal;
+    game.wait = REVEAL_TIME;
+    feedback.sounds.send(PlaySoundEvent(SoundType::Result));
+}
+
+/// Guess mode: the answerer clicks a card in secret, then the predictor clicks the card they
+/// think was chosen. Running out of time while predicting counts as a wrong guess.
+fn guess_input(
+    actions: Res<Actions>,
+    mut game: ResMut<Game>,
+    settings: Res<Settings>,
+    qs: Res<Questions>,
+    source: Res<DeckSource>,
+    db: Db,
+    mut feedback: Feedback,
+) {
+    if !matches!(game.phase, Phase::SecretAnswer | Phase::Predict) {
+        return;
+    }
+
+    // With two gamepads each player answers on their own, a single one gets passed around
+    let player = if game.phase == Phase::SecretAnswer { game.guess.answerer } else { game.guess.predictor() };
+    let pads = &actions.pads;
+    let pad = if pads.len() == 1 { pads.first() } else { pads.get(player) };
+    let clicked = actions.local.choice().or_else(|| pad.and_then(|pad| pad.choice()));
+    if clicked.is_some() {
+        feedback.rumble.send(RumbleEvent::pick(Some(player).filter(|_| pads.len() > 1), game.tremble));
+    }
+    let reaction_time = QUESTION_TIME - game.timer;
+
+    if game.phase == Phase::SecretAnswer {
+        let Some(choice) = clicked else {
+            return;
+        };
+        game.guess.secret = Some(choice);
+        let answerer = game.guess.answerer;
+        game.record_answer(answerer, choice);
+        game.total_reaction_time += reaction_time;
+        game.answers_count += 1;
+        game.last_reaction = reaction_time;
+
+        let mut record = ResponseRecord::new(&mut game, &qs, *source, Some(choice), Some(reaction_time));
+        record.player = Some(PLAYER_NAMES[game.guess.answerer].into());
+        record_response(&db, record);
+
+        // Same cards, fresh clock for the other player
+        game.phase = Phase::Predict;
+        game.timer = QUESTION_TIME;
+        game.last_tick = 5;
+        game.hovered_card = None;
+        feedback.sounds.send(PlaySoundEvent(SoundType::Whoosh));
+        return;
+    }
+
+    if clicked.is_none() && game.timer > 0.0 {
+        return;
+    }
+    let predictor = game.guess.predictor();
+    game.guess.prediction = clicked;
+    game.guess.guesses[predictor] += 1;
+
+    if clicked.is_some() && clicked == game.guess.secret {
+        game.guess.correct[predictor] += 1;
+        game.streak += 1;
+        feedback.fireworks.send(SpawnFireworksEvent {
+            x: 0.0,
+            intensity: game.streak,
+        });
+    } else {
+        game.streak = 0;
+    }
+    game.register_answer(&settings.rules());
+
+    game.phase = Phase::Reveal;
+    game.wait = REVEAL_TIME;
+    feedback.sounds.send(PlaySoundEvent(SoundType::Result));
+}
+
+fn reveal_tick(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    settings: Res<Settings>,
+    mut reveal_text: Query<(&mut Text2d, &mut TextColor, &mut Visibility, &mut Transform), With<RevealText>>,
+    mut timer_vis: Query<&mut Visibility, (With<TimerDisplay>, Without<RevealText>)>,
+    mut sound_events: EventWriter<PlaySoundEvent>,
+) {
+    if game.phase != Phase::Reveal {
+        for (_, _, mut v, _) in reveal_text.iter_mut() {
+            *v = Visibility::Hidden;
+        }
+        return;
+    }
+
+    let (text, color) = if settings.mode == GameMode::Guess {
+        let guess = &game.guess;
+        let (predictor, answerer) = (PLAYER_NAMES[guess.predictor()], PLAYER_NAMES[guess.answerer]);
+        match guess.prediction {
+            None => (format!("TOO SLOW, {}!", predictor), TEXT_YELLOW),
+            Some(p) if Some(p) == guess.secret => (format!("{} KNOWS {}!", predictor, answerer), RESULT_GREEN),
+            Some(_) => (format!("NOPE! {} FOOLED YOU", answerer), CARD_LEFT),
+        }
+    } else {
+        match game.couples.picks {
+            [Some(a), Some(b)] if a == b => ("MATCH!".to_string(), RESULT_GREEN),
+            [Some(_), Some(_)] => ("NO MATCH".to_string(), CARD_LEFT),
+            [None, _] => (format!("TOO SLOW, {}!", PLAYER_NAMES[0]), TEXT_YELLOW),
+            _ => (format!("TOO SLOW, {}!", PLAYER_NAMES[1]), TEXT_YELLOW),
+        }
+    };
+    // Pops in big and settles
+    let elapsed = REVEAL_TIME - game.wait;
+    let pop = 1.0 + (1.0 - (elapsed * 4.0).min(1.0)) * 0.6;
+    for (mut txt, mut col, mut v, mut t) in reveal_text.iter_mut() {
+        txt.0 = text.clone();
+        col.0 = color;
+        *v = Visibility::Visible;
+        t.scale = Vec3::splat(pop);
+    }
+    for mut v in timer_vis.iter_mut() {
+        *v = Visibility::Hidden;
+    }
+
+    game.wait -= time.delta_secs();
+    if game.wait <= 0.0 {
+        game.couples.picks = [None; 2];
+        game.guess.swap_roles();
+        game.phase = Phase::Transition;
+        game.wait = 0.4;
+        sound_events.send(PlaySoundEvent(SoundType::Go));
+    }
+}
+
+/// Couples mode: key hints at the sides while answering ("LOCKED IN" once a partner has
+/// answered, without saying which way), then each name on the card that partner picked.
+/// Guess mode: only whoever's turn it is, then the secret answer and the guess.
+fn sync_player_badges(
+    game: Res<Game>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    bindings: Res<Bindings>,
+    mut badges: Query<(&PlayerBadge, &mut Text2d, &mut Transform, &mut Visibility)>,
+) {
+    let showing = settings.mode != GameMode::Solo && (game.phase.takes_answers() || game.phase == Phase::Reveal);
+    let picks = match settings.mode {
+        GameMode::Guess => game.guess.picks(),
+        _ => game.couples.picks,
+    };
+    let both_same = matches!(picks, [Some(a), Some(b)] if a == b);
+
+    for (badge, mut txt, mut t, mut v) in badges.iter_mut() {
+        if !showing {
+            *v = Visibility::Hidden;
+            continue;
+        }
+        *v = Visibility::Visible;
+        let name = PLAYER_NAMES[badge.player];
+        let side = if badge.player == 0 { -1.0 } else { 1.0 };
+        let pick = picks[badge.player];
+
+        if settings.mode == GameMode::Guess && game.phase != Phase::Reveal {
+            let (turn, hint) = match game.phase {
+                Phase::SecretAnswer => (game.guess.answerer, "ANSWER IN\nSECRET".to_string()),
+                _ => (game.guess.predictor(), format!("WHAT DID\n{} PICK?", PLAYER_NAMES[game.guess.answerer])),
+            };
+            if badge.player != turn {
+                *v = Visibility::Hidden;
+                continue;
+            }
+            txt.0 = format!("{}\n{}", name, hint);
+            t.translation = Vec3::new(side * 520.0, -20.0, 12.0);
+            continue;
+        }
+
+        match (game.phase, pick) {
+            (Phase::Reveal, Some(choice)) => {
+                let card_x = choice.card_x();
+                let nudge = if both_same { side * 60.0 } else { 0.0 };
+                let bounce = (time.elapsed_secs() * 12.0).sin().abs() * 8.0;
+                txt.0 = name.into();
+                t.translation = Vec3::new(card_x + nudge, -240.0 + bounce, 12.0);
+            }
+            (Phase::Reveal, None) => {
+                txt.0 = format!("{}\nTOO SLOW", name);
+                t.translation = Vec3::new(side * 520.0, -20.0, 12.0);
+            }
+            (_, Some(_)) => {
+                txt.0 = format!("{}\nLOCKED IN", name);
+                t.translation = Vec3::new(side * 520.0, -20.0, 12.0);
+            }
+            (_, None) => {
+                let keys = [Choice::Left, Choice::Right].map(|c| bindings.hint(Action::player(badge.player, c)));
+                txt.0 = format!("{}\n{} / {}", name, keys[0], keys[1]);
+                t.translation = Vec3::new(side * 520.0, -20.0, 12.0);
+            }
+        }
+    }
+}
+
 fn transition_tick(
     time: Res<Time>,
     mut game: ResMut<Game>,
-    qs: Res<Questions>,
-    mut cards: Query<(&Card, &mut Transform, &mut Visibility), Without<CardLabel>>,
-    mut labels: Query<(&CardLabel, &mut Text2d, &mut Transform, &mut Visibility), Without<Card>>,
-    mut title: Query<(&mut Text2d, &mut Visibility), (With<TitleText>, Without<CardLabel>, Without<Card>)>,
-    mut timer_vis: Query<&mut Visibility, (With<TimerDisplay>, Without<TitleText>, Without<CardLabel>, Without<Card>)>,
-    mut go_text: Query<(&mut Visibility, &mut Transform), (With<GoText>, Without<Card>, Without<CardLabel>, Without<TitleText>, Without<TimerDisplay>)>,
+    deck: Deck,
+    mut table: Table,
+    mut hud: Hud,
+    mut go_text: Query<(&mut Visibility, &mut Transform), OffTable<GoText>>,
     mut sound_events: EventWriter<PlaySoundEvent>,
 ) {
     if game.phase != Phase::Transition {
@@ -1028,25 +4441,18 @@ fn transition_tick(
             *v = Visibility::Hidden;
         }
 
-        let mut rng = rand::rng();
-        let available: Vec<usize> = (0..qs.0.len())
-            .filter(|i| !game.used_questions.contains(i))
-            .collect();
-        
-        if available.is_empty() {
-            game.phase = Phase::Results;
+        let Some(new_question) = deck.advance(&mut game) else {
+            game.phase = end_turn(&mut game, &deck.settings);
             sound_events.send(PlaySoundEvent(SoundType::Result));
             return;
-        }
-        
-        let new_question = available[rng.random_range(0..available.len())];
+        };
+
         game.question = new_question;
-        game.used_questions.push(new_question);
 
-        let q = &qs.0[game.question];
-        game.phase = Phase::Playing;
-        
-        let time_pressure = if game.answers_count < 20 {
+        let q = &deck.qs.0[game.question];
+        game.phase = deck.settings.mode.question_phase();
+
+        let time_pressure = if game.answers_count < deck.settings.length.pressure_start() {
             QUESTION_TIME
         } else {
             let avg_reaction = game.total_reaction_time / game.answers_count as f32;
@@ -1058,251 +4464,306 @@ fn transition_tick(
 
         sound_events.send(PlaySoundEvent(SoundType::CardIn));
 
-        for (card, mut t, mut v) in cards.iter_mut() {
-            *v = Visibility::Visible;
-            t.scale = Vec3::ONE;
-            t.translation.x = if card.choice == Choice::Left {
-                -CARD_GAP / 2.0
-            } else {
-                CARD_GAP / 2.0
-            };
-        }
-
-        for (lbl, mut txt, mut t, mut v) in labels.iter_mut() {
-            *v = Visibility::Visible;
-            t.scale = Vec3::ONE;
-            match lbl.choice {
-                Choice::Left => {
-                    txt.0 = q.left.to_string();
-                }
-                Choice::Right => {
-                    txt.0 = q.right.to_string();
-                    t.translation.x = CARD_GAP / 2.0;
-                }
-            }
-        }
-
-        for (mut txt, mut vis) in title.iter_mut() {
-            txt.0 = q.title.into();
-            *vis = Visibility::Visible;
-        }
-
-        for mut v in timer_vis.iter_mut() {
-            *v = Visibility::Visible;
-        }
+        table.deal(q);
+        hud.show_text(HudText::Title, q.title.clone());
+        hud.show(HudText::Timer, true);
     }
 }
 
 fn show_results(
     mut game: ResMut<Game>,
-    mut db_stats: ResMut<DbStats>,
-    mut cards: Query<&mut Transform, (With<Card>, Without<CardLabel>)>,
-    mut labels: Query<&mut Transform, (With<CardLabel>, Without<Card>)>,
-    mut title: Query<(&mut Text2d, &mut Visibility), (With<TitleText>, Without<CardLabel>, Without<Card>)>,
-    mut result: Query<(&mut Text2d, &mut Visibility), (With<ResultDisplay>, Without<TitleText>, Without<CardLabel>, Without<Card>)>,
-    mut stats_display: Query<(&mut Text2d, &mut Visibility), (With<StatsDisplay>, Without<ResultDisplay>, Without<TitleText>, Without<CardLabel>, Without<Card>)>,
-    mut timer_vis: Query<&mut Visibility, (With<TimerDisplay>, Without<TitleText>, Without<ResultDisplay>, Without<CardLabel>, Without<Card>, Without<StatsDisplay>)>,
-    mut replay_text: Query<&mut Text2d, (With<ReplayInstruction>, Without<TitleText>, Without<ResultDisplay>, Without<CardLabel>, Without<Card>, Without<StatsDisplay>)>,
-    db_pool: Res<DbPool>,
-    runtime: Res<TokioRuntime>,
+    db_stats: Res<DbStats>,
+    mut table: Table,
+    mut hud: Hud,
+    settings: Res<Settings>,
+    db: Db,
 ) {
     if game.phase != Phase::Results || game.results_shown {
         return;
     }
     game.results_shown = true;
 
-    let (res, result_type) = if game.score_l > game.score_r {
-        ("You're a CHAOTIC GREMLIN!", "chaotic_gremlin")
-    } else if game.score_r > game.score_l {
-        ("You're a FUNCTIONING ADULT!", "functioning_adult")
+    let (score_left, score_right) = game.totals();
+    let compatibility = game.compatibility(settings.mode);
+    let (res, result_type) = if settings.mode == GameMode::Couples {
+        match compatibility {
+            Some(pct) => (format!("{}% COMPATIBLE!", pct), "couples"),
+            None => ("NOTHING TO COMPARE!".to_string(), "couples"),
+        }
+    } else if settings.mode == GameMode::Guess {
+        match compatibility {
+            Some(pct) => (format!("YOU KNOW EACH OTHER {}%!", pct), "guess"),
+            None => ("NOTHING TO GUESS!".to_string(), "guess"),
+        }
+    } else if settings.mode == GameMode::Party {
+        match game.alike_pairs().first() {
+            Some(&(a, b, _)) => (format!("MOST ALIKE: {} & {}!", game.players[a].name, game.players[b].name), "party"),
+            None => ("NOBODY ANSWERED!".to_string(), "party"),
+        }
+    } else if score_left > score_right {
+        ("You're a CHAOTIC GREMLIN!".to_string(), "chaotic_gremlin")
+    } else if score_right > score_left {
+        ("You're a FUNCTIONING ADULT!".to_string(), "functioning_adult")
     } else {
-        ("You're PERFECTLY BALANCED!", "perfectly_balanced")
+        ("You're PERFECTLY BALANCED!".to_string(), "perfectly_balanced")
     };
 
-    let pool_arc = db_pool.0.clone();
+    let finished = game.finished_session(settings.mode, result_type);
+    // Stats are read back after the write so this session counts towards them
     let session_id = game.session_id.clone();
-    let score_l = game.score_l;
-    let score_r = game.score_r;
-    let result_type_owned = result_type.to_string();
-    let total = (score_l + score_r) as f64;
-    let my_left_pct = if total > 0.0 { (score_l as f64 / total) * 100.0 } else { 50.0 };
-
-    runtime.0.spawn(async move {
-        let lock = pool_arc.lock().await;
-        if let Some(ref pool) = *lock {
-            let query = "INSERT INTO game_scores (session_id, score_left, score_right, result_type) VALUES (?, ?, ?, ?)";
-            let _ = sqlx::query(query)
-                .bind(&session_id)
-                .bind(score_l)
-                .bind(score_r)
-                .bind(&result_type_owned)
-                .execute(pool)
-                .await;
+    let want_stats = score_left + score_right >= 3;
+    db.write_then(ScoreWrite::Finished(finished), move |store| async move {
+        if !want_stats {
+            return Ok(DbResult::Saved("game score"));
         }
+        let stats = store.stats().await?;
+        let top = store.leaderboard(3).await?;
+        Ok(DbResult::Stats(DbStats {
+            loaded: stats.players >= 3,
+            total_players: stats.players,
+            avg_left_pct: stats.avg_left_pct,
+            top_runs: top.into_iter().map(|e| (e.answers, e.session_id == session_id)).collect(),
+        }))
     });
 
-    if !db_stats.loaded && score_l + score_r >= 3 {
-        let pool_arc2 = db_pool.0.clone();
-        let stats_ptr = std::ptr::addr_of_mut!(*db_stats) as usize;
-        
-        runtime.0.spawn(async move {
-            let lock = pool_arc2.lock().await;
-            if let Some(ref pool) = *lock {
-                let row: Option<(i64, f64)> = sqlx::query_as(
-                    "SELECT COUNT(*), AVG(score_left * 100.0 / (score_left + score_right)) FROM game_scores WHERE score_left + score_right >= 3"
-                )
-                .fetch_optional(pool)
-                .await
-                .ok()
-                .flatten();
-                
-                if let Some((count, avg)) = row {
-                    if count >= 3 {
-                        unsafe {
-                            let stats = &mut *(stats_ptr as *mut DbStats);
-                            stats.total_players = count;
-                            stats.avg_left_pct = avg;
-                            stats.loaded = true;
-                        }
-                    }
-                }
+    let stats_text = stats_text(&db_stats, &game);
+
+    hud.show_text(HudText::Result, res);
+    let summary = match settings.mode {
+        GameMode::Guess => Some(guess_summary(&game.guess)),
+        GameMode::Party => Some(party_summary(&game)),
+        GameMode::Couples => Some(couples_summary(&game.couples)),
+        GameMode::Solo => {
+            let auto_picks = game.auto_picks();
+            let mut lines: Vec<String> = game
+                .dominant_traits()
+                .into_iter()
+                .take(3)
+                .map(|(name, value, _)| format!("{}: {}", name.replace('_', " ").to_uppercase(), value))
+                .collect();
+            if !lines.is_empty() {
+                lines.insert(0, "YOUR TOP TRAITS".into());
             }
-        });
-    }
-
-    let stats_text = if db_stats.loaded && db_stats.total_players >= 3 {
-        format!(
-            "{} players | Avg: {:.0}% chaotic vs {:.0}% adult\nYou: {:.0}% chaotic",
-            db_stats.total_players,
-            db_stats.avg_left_pct,
-            100.0 - db_stats.avg_left_pct,
-            my_left_pct
-        )
-    } else {
-        String::new()
+            if auto_picks > 0 {
+                lines.push(format!("({} picked for you when time ran out)", auto_picks));
+            }
+            (!lines.is_empty()).then(|| lines.join("\n"))
+        }
     };
-
-    for (mut txt, mut vis) in result.iter_mut() {
-        txt.0 = res.into();
-        *vis = Visibility::Visible;
+    if let Some(summary) = summary {
+        hud.show_text(HudText::Traits, summary);
     }
-    for (mut txt, mut vis) in stats_display.iter_mut() {
-        if !stats_text.is_empty() {
-            txt.0 = stats_text.clone();
-            *vis = Visibility::Visible;
-        }
+    if !stats_text.is_empty() {
+        hud.show_text(HudText::Stats, stats_text);
     }
-    for mut t in cards.iter_mut() {
+    for (_, mut t, _) in table.cards.iter_mut() {
         t.scale = Vec3::ZERO;
     }
-    for mut t in labels.iter_mut() {
+    for (_, _, mut t, _) in table.labels.iter_mut() {
         t.scale = Vec3::ZERO;
     }
-    for mut v in timer_vis.iter_mut() {
-        *v = Visibility::Hidden;
+    hud.show(HudText::Timer, false);
+    let restart = hud.bindings.hint(Action::Restart);
+    let hint = format!(
+        "Press {} to restart, {} to change categories, {} for the menu",
+        restart,
+        hud.bindings.hint(Action::ChangeCategories),
+        hud.bindings.hint(Action::MainMenu)
+    );
+    hud.show_text(HudText::Title, format!("Press {} to play again!", restart));
+    hud.write(HudText::Hint, hint);
+}
+
+/// Couples results: the questions the partners disagreed on
+fn couples_summary(couples: &CouplesTally) -> String {
+    const SHOWN: usize = 4;
+    if couples.answered == 0 {
+        return "Answer together next time!".into();
     }
-    for (mut txt, mut vis) in title.iter_mut() {
-        txt.0 = "Press R to play again!".into();
-        *vis = Visibility::Visible;
+    if couples.disagreements.is_empty() {
+        return "YOU AGREED ON EVERYTHING!".into();
+    }
+    let mut lines: Vec<String> = couples
+        .disagreements
+        .iter()
+        .take(SHOWN)
+        .map(|(title, a, b)| format!("{}  {}: {} / {}: {}", title, PLAYER_NAMES[0], a, PLAYER_NAMES[1], b))
+        .collect();
+    if couples.disagreements.len() > SHOWN {
+        lines.push(format!("...and {} more", couples.disagreements.len() - SHOWN));
+    }
+    format!("YOU DISAGREED ON\n{}", lines.join("\n"))
+}
+
+/// Party results: the pairs who answered most alike
+fn party_summary(game: &Game) -> String {
+    const SHOWN: usize = 3;
+    let pairs = game.alike_pairs();
+    if pairs.is_empty() {
+        return String::new();
+    }
+    let lines: Vec<String> = pairs
+        .iter()
+        .take(SHOWN)
+        .map(|&(a, b, pct)| format!("{} & {}  {}% alike", game.players[a].name, game.players[b].name, pct))
+        .collect();
+    format!("CLOSEST PAIRS\n{}", lines.join("\n"))
+}
+
+/// Guess results: how often each player read the other right
+fn guess_summary(guess: &GuessTally) -> String {
+    let lines: Vec<String> = (0..2)
+        .map(|p| {
+            format!(
+                "{} guessed {} right {} / {}",
+                PLAYER_NAMES[p],
+                PLAYER_NAMES[1 - p],
+                guess.correct[p],
+                guess.guesses[p]
+            )
+        })
+        .collect();
+    format!("HOW WELL DO YOU KNOW THEM?\n{}", lines.join("\n"))
+}
+
+/// Line comparing this session with everyone else, empty until enough games are recorded
+fn stats_text(db_stats: &DbStats, game: &Game) -> String {
+    if !db_stats.loaded || db_stats.total_players < 3 {
+        return String::new();
+    }
+    let (left, right) = game.totals();
+    let total = (left + right) as f64;
+    let my_left_pct = if total > 0.0 { (left as f64 / total) * 100.0 } else { 50.0 };
+    let mut text = format!(
+        "{} players | Avg: {:.0}% chaotic vs {:.0}% adult\nYou: {:.0}% chaotic",
+        db_stats.total_players,
+        db_stats.avg_left_pct,
+        100.0 - db_stats.avg_left_pct,
+        my_left_pct
+    );
+    if !db_stats.top_runs.is_empty() {
+        let runs: Vec<String> = db_stats
+            .top_runs
+            .iter()
+            .map(|(answers, mine)| if *mine { format!("{} (you)", answers) } else { answers.to_string() })
+            .collect();
+        text.push_str(&format!("\nLongest runs: {}", runs.join(" · ")));
+    }
+    text
+}
+
+/// Stats usually arrive a few frames after the results screen opens
+fn refresh_stats_display(
+    game: Res<Game>,
+    db_stats: Res<DbStats>,
+    mut stats_display: Query<(&mut Text2d, &mut Visibility), With<StatsDisplay>>,
+) {
+    if game.phase != Phase::Results || !db_stats.is_changed() {
+        return;
+    }
+    let text = stats_text(&db_stats, &game);
+    if text.is_empty() {
+        return;
     }
-    for mut txt in replay_text.iter_mut() {
-        txt.0 = "Press R to restart".into();
+    for (mut txt, mut vis) in stats_display.iter_mut() {
+        txt.0 = text.clone();
+        *vis = Visibility::Visible;
     }
 }
 
 fn handle_replay(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<Actions>,
     mut game: ResMut<Game>,
-    qs: Res<Questions>,
-    mut cards: Query<(&Card, &mut Visibility, &mut Transform), Without<CardLabel>>,
-    mut labels: Query<(&CardLabel, &mut Text2d, &mut Visibility, &mut Transform), Without<Card>>,
-    mut title: Query<(&mut Text2d, &mut Visibility), (With<TitleText>, Without<CardLabel>, Without<Card>)>,
-    mut result: Query<&mut Visibility, (With<ResultDisplay>, Without<TitleText>, Without<CardLabel>, Without<Card>)>,
-    mut stats_vis: Query<&mut Visibility, (With<StatsDisplay>, Without<ResultDisplay>, Without<TitleText>, Without<CardLabel>, Without<Card>)>,
-    mut timer_vis: Query<&mut Visibility, (With<TimerDisplay>, Without<TitleText>, Without<ResultDisplay>, Without<CardLabel>, Without<Card>, Without<StatsDisplay>)>,
-    mut replay_text: Query<&mut Text2d, (With<ReplayInstruction>, Without<TitleText>, Without<ResultDisplay>, Without<CardLabel>, Without<Card>, Without<StatsDisplay>)>,
+    deck: Deck,
+    mut table: Table,
+    mut hud: Hud,
+    db: Db,
     mut sound_events: EventWriter<PlaySoundEvent>,
 ) {
-    if game.phase != Phase::Results {
+    let restart = game.restart_requested;
+    if game.phase != Phase::Results && !restart {
         return;
     }
+    game.restart_requested = false;
 
-    if keyboard.just_pressed(KeyCode::KeyR) {
-        game.phase = Phase::Playing;
-        game.timer = QUESTION_TIME;
-        game.question = 0;
-        game.score_l = 0;
-        game.score_r = 0;
-        game.picked = None;
-        game.wait = 0.0;
-        game.session_id = uuid::Uuid::new_v4().to_string();
-        game.last_tick = 5;
-        game.hovered_card = None;
-        game.results_shown = false;
-        game.timeouts = 0;
-        game.streak = 0;
-        game.used_questions = vec![0];
-        game.total_reaction_time = 0.0;
-        game.answers_count = 0;
-        game.last_reaction = 5.0;
-        game.tremble = 0.0;
-
-        sound_events.send(PlaySoundEvent(SoundType::CardIn));
-
-        let q = &qs.0[0];
-
-        for (card, mut vis, mut t) in cards.iter_mut() {
-            *vis = Visibility::Visible;
-            t.scale = Vec3::ONE;
-            // Reset position
-            t.translation.x = if card.choice == Choice::Left {
-                -CARD_GAP / 2.0
-            } else {
-                CARD_GAP / 2.0
-            };
-        }
+    let replay = actions.just_pressed(Action::Restart);
+    let to_menu = if actions.just_pressed(Action::ChangeCategories) {
+        Some((Phase::Categories, CATEGORY_HINT))
+    } else if actions.just_pressed(Action::MainMenu) {
+        Some((Phase::MainMenu, MAIN_MENU_HINT))
+    } else {
+        None
+    };
+    if to_menu.is_some() || replay {
+        hud.show(HudText::Traits, false);
+    }
 
-        for (lbl, mut txt, mut vis, mut t) in labels.iter_mut() {
-            *vis = Visibility::Visible;
-            t.scale = Vec3::ONE;
-            match lbl.choice {
-                Choice::Left => txt.0 = q.left.to_string(),
-                Choice::Right => txt.0 = q.right.to_string(),
-            }
-        }
+    // A session restarted before its results is still recorded, marked as abandoned
+    if restart && !game.results_shown {
+        db.write(ScoreWrite::Finished(game.finished_session(deck.settings.mode, ABANDONED)));
+    }
 
-        for (mut txt, mut vis) in title.iter_mut() {
-            txt.0 = q.title.into();
-            *vis = Visibility::Visible;
+    let mut menu = to_menu.filter(|_| !restart);
+    // Draw the replay's first question up front, going back to the categories if none match
+    let mut first = None;
+    if menu.is_none() && (replay || restart) {
+        game.reset_for_session(&deck.settings);
+        first = deck.next(&game);
+        if first.is_none() {
+            menu = Some((Phase::Categories, NO_QUESTIONS_HINT));
         }
+    }
 
-        for mut vis in result.iter_mut() {
-            *vis = Visibility::Hidden;
-        }
+    // Back to the category or main menu for a fresh session
+    if let Some((phase, hint)) = menu {
+        *game = Game::default();
+        game.phase = phase;
+        hud.write(HudText::Hint, hint);
+        hud.show_text(HudText::Title, "TWO PEOPLE");
+        hud.show(HudText::Hurry, true);
+        hud.show(HudText::Result, false);
+        hud.show(HudText::Stats, false);
+        sound_events.send(PlaySoundEvent(SoundType::Whoosh));
+        return;
+    }
 
-        for mut vis in stats_vis.iter_mut() {
-            *vis = Visibility::Hidden;
+    if let Some(first) = first {
+        // A party starts again from the first player's handover
+        game.phase = match deck.settings.mode {
+            GameMode::Party => Phase::Handover,
+            mode => mode.question_phase(),
+        };
+        game.wait = 0.0;
+        game.question = first;
+        game.used_questions.push(first);
+        game.party_questions.push(first);
+        // Parties go through the intro again, which records the start
+        if deck.settings.mode != GameMode::Party {
+            record_session_start(&db, game.session_id.clone());
         }
 
-        for mut vis in timer_vis.iter_mut() {
-            *vis = Visibility::Visible;
-        }
+        sound_events.send(PlaySoundEvent(SoundType::CardIn));
 
-        for mut txt in replay_text.iter_mut() {
-            txt.0 = "Click a card to choose!".into();
-        }
+        let q = &deck.qs.0[game.question];
+        table.deal(q);
+        hud.show_text(HudText::Title, q.title.clone());
+        hud.show(HudText::Result, false);
+        hud.show(HudText::Stats, false);
+        hud.show(HudText::Timer, true);
+        hud.play_hint(deck.settings.mode);
 
         info!("Game restarted with new session: {}", game.session_id);
     }
 }
 
+
 fn update_visuals(
     time: Res<Time>,
     game: Res<Game>,
     mut timer_q: Query<(&mut Text2d, &mut TextColor, &mut Transform), With<TimerDisplay>>,
     mut title_q: Query<(&mut Visibility, &mut Transform), (With<TitleText>, Without<TimerDisplay>)>,
 ) {
-    if game.phase == Phase::Playing {
+    if game.phase.takes_answers() {
         let secs = game.timer.ceil() as i32;
         let frac = game.timer.fract();
         let intensity = ((game.answers_count as f32 - 3.0).max(0.0) / 10.0).min(2.0);
@@ -1374,9 +4835,11 @@ fn animate_bg_shapes(time: Res<Time>, mut shapes: Query<(&mut Transform, &BgShap
     }
 }
 
-fn animate_pulse(time: Res<Time>, mut q: Query<(&mut Transform, &Pulse)>) {
+fn animate_pulse(time: Res<Time>, game: Res<Game>, mut q: Query<(&mut Transform, &Pulse)>) {
+    // Beats harder and faster during the intro, on the way out of the menus
+    let (speed, depth) = if game.phase == Phase::Intro { (4.0, 0.15) } else { (1.0, 0.05) };
     for (mut t, p) in q.iter_mut() {
-        let s = 1.0 + (time.elapsed_secs() * p.speed).sin() * 0.05;
+        let s = 1.0 + (time.elapsed_secs() * p.speed * speed).sin() * depth;
         t.scale = Vec3::splat(s);
     }
 }
@@ -1488,12 +4951,10 @@ fn animate_fireworks(
 fn uhoh_tick(
     time: Res<Time>,
     mut game: ResMut<Game>,
-    qs: Res<Questions>,
-    mut uhoh_text: Query<(&mut Visibility, &mut Transform), (With<UhOhText>, Without<Card>, Without<CardLabel>)>,
-    mut cards: Query<(&Card, &mut Transform, &mut Visibility), Without<CardLabel>>,
-    mut labels: Query<(&CardLabel, &mut Text2d, &mut Transform, &mut Visibility), Without<Card>>,
-    mut title: Query<&mut Visibility, (With<TitleText>, Without<Card>, Without<CardLabel>, Without<UhOhText>)>,
-    mut timer_vis: Query<&mut Visibility, (With<TimerDisplay>, Without<TitleText>, Without<Card>, Without<CardLabel>, Without<UhOhText>)>,
+    deck: Deck,
+    mut uhoh_text: Query<(&mut Visibility, &mut Transform), OffTable<UhOhText>>,
+    mut table: Table,
+    mut hud: Hud,
     mut sound_events: EventWriter<PlaySoundEvent>,
 ) {
     if game.phase != Phase::UhOh {
@@ -1509,51 +4970,80 @@ fn uhoh_tick(
         t.scale = Vec3::splat(pulse);
     }
 
-    for mut v in title.iter_mut() {
-        *v = Visibility::Hidden;
-    }
-    for mut v in timer_vis.iter_mut() {
-        *v = Visibility::Hidden;
-    }
+    hud.show(HudText::Title, false);
+    hud.show(HudText::Timer, false);
 
     game.wait -= time.delta_secs();
     if game.wait <= 0.0 {
-        game.question += 1;
-        if game.question >= qs.0.len() {
-            game.question = 0;
-        }
+        let Some(next) = deck.advance(&mut game) else {
+            game.phase = end_turn(&mut game, &deck.settings);
+            sound_events.send(PlaySoundEvent(SoundType::Result));
+            return;
+        };
+        game.question = next;
 
-        let q = &qs.0[game.question];
+        let q = &deck.qs.0[game.question];
+        hud.write(HudText::Title, q.title.clone());
         game.timer = QUESTION_TIME;
         game.last_tick = 5;
         game.picked = None;
-        game.phase = Phase::Playing;
+        game.phase = deck.settings.mode.question_phase();
 
-        for (card, mut t, mut vis) in cards.iter_mut() {
-            *vis = Visibility::Visible;
-            t.scale = Vec3::ONE;
-            t.translation.x = if card.choice == Choice::Left {
-                -CARD_GAP / 2.0
-            } else {
-                CARD_GAP / 2.0
-            };
-        }
+        table.deal(q);
 
-        for (lbl, mut txt, mut t, mut vis) in labels.iter_mut() {
-            *vis = Visibility::Visible;
-            t.scale = Vec3::ONE;
-            match lbl.choice {
-                Choice::Left => {
-                    txt.0 = q.left.to_string();
-                    t.translation.x = -CARD_GAP / 2.0;
-                }
-                Choice::Right => {
-                    txt.0 = q.right.to_string();
-                    t.translation.x = CARD_GAP / 2.0;
-                }
-            }
+        sound_events.send(PlaySoundEvent(SoundType::CardIn));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(left: &str, right: &str) -> Q {
+        Q {
+            id: 0,
+            title: format!("{} or {}?", left, right),
+            left: left.into(),
+            left_em: String::new(),
+            right: right.into(),
+            right_em: String::new(),
+            trait_name: "sleep_schedule".into(),
+            category: "lifestyle".into(),
+            rating: None,
         }
+    }
 
-        sound_events.send(PlaySoundEvent(SoundType::CardIn));
+    #[test]
+    fn traits_count_the_option_picked_not_the_side() {
+        let mut game = Game::default();
+        let owl = question("Early Bird", "Night Owl");
+        let snooze = question("Up & At 'Em", "Snooze x100");
+        game.record_trait(&owl, Choice::Right);
+        game.record_trait(&snooze, Choice::Left);
+        game.record_trait(&snooze, Choice::Right);
+        game.record_trait(&owl, Choice::Right);
+
+        // Two rights across different questions don't add up to one option, and the last
+        // question asked doesn't get to relabel the others
+        assert_eq!(game.dominant_traits(), [("sleep_schedule", "Night Owl", 2)]);
+    }
+
+    #[test]
+    fn tied_trait_has_no_lean() {
+        let mut game = Game::default();
+        game.record_trait(&question("Early Bird", "Night Owl"), Choice::Left);
+        game.record_trait(&question("Up & At 'Em", "Snooze x100"), Choice::Right);
+        assert!(game.dominant_traits().is_empty());
+    }
+
+    #[test]
+    fn reloaded_deck_keeps_the_questions_asked() {
+        let before = Questions(vec![question("Tea", "Coffee"), question("Cats", "Dogs"), question("Beach", "Hills")]);
+        let asked = before.titles(&[2, 0]);
+        let after = Questions(vec![question("Beach", "Hills"), question("Sun", "Rain"), question("Tea", "Coffee")]);
+        assert_eq!(after.positions(&asked), [0, 2]);
+        // A question dropped from the deck is forgotten
+        assert!(after.positions(&before.titles(&[1])).is_empty());
     }
 }
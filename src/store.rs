@@ -0,0 +1,1246 @@
+//! Score storage. `DATABASE_URL` picks the backend by its scheme:
+//! `mysql://`, `sqlite:`, `jsonl://path/to/file.jsonl` or `memory:`.
+
+use crate::{Choice, Q};
+use bevy::log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    error::BoxDynError,
+    migrate::{Migrate, Migration, MigrationSource, Migrator},
+    mysql::{MySqlDatabaseError, MySqlPoolOptions},
+    sqlite::{SqliteConnectOptions, SqliteError, SqlitePoolOptions},
+    MySqlPool, Row, SqlitePool,
+};
+use std::collections::HashSet;
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Whether a failed write is worth trying again
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorKind {
+    /// The store couldn't be reached or gave up (connection, pool timeout, I/O)
+    Transient,
+    /// The store refused the data itself (a constraint, truncation or bad value), so retrying
+    /// the same write can never work
+    Data,
+}
+
+#[derive(Debug)]
+pub struct StoreError {
+    pub kind: ErrorKind,
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl StoreError {
+    pub fn is_transient(&self) -> bool {
+        self.kind == ErrorKind::Transient
+    }
+}
+
+impl<E: Into<Box<dyn std::error::Error + Send + Sync>>> From<E> for StoreError {
+    fn from(e: E) -> Self {
+        let source = e.into();
+        let kind = error_kind(source.as_ref());
+        Self { kind, source }
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+/// Sorts errors into the ones a retry could fix and the ones it can't. Anything unrecognised
+/// counts as transient, so a write is only given up on when the store clearly rejected it.
+fn error_kind(e: &(dyn std::error::Error + Send + Sync + 'static)) -> ErrorKind {
+    if e.is::<serde_json::Error>() {
+        return ErrorKind::Data;
+    }
+    let Some(e) = e.downcast_ref::<sqlx::Error>() else {
+        return ErrorKind::Transient;
+    };
+    match e {
+        sqlx::Error::Database(db) => {
+            // MySQL reports an SQLSTATE: class 22 is bad data (truncation, out of range),
+            // class 23 a broken constraint. SQLite's primary codes 18-20 are TOOBIG,
+            // CONSTRAINT and MISMATCH.
+            let code = db.code().unwrap_or_default();
+            let rejected = db.kind() != sqlx::error::ErrorKind::Other
+                || (db.try_downcast_ref::<MySqlDatabaseError>().is_some()
+                    && (code.starts_with("22") || code.starts_with("23")))
+                || (db.try_downcast_ref::<SqliteError>().is_some()
+                    && code.parse::<i32>().is_ok_and(|c| (18..=20).contains(&(c & 0xff))));
+            if rejected { ErrorKind::Data } else { ErrorKind::Transient }
+        }
+        sqlx::Error::Encode(_)
+        | sqlx::Error::Decode(_)
+        | sqlx::Error::ColumnDecode { .. }
+        | sqlx::Error::ColumnNotFound(_)
+        | sqlx::Error::TypeNotFound { .. } => ErrorKind::Data,
+        _ => ErrorKind::Transient,
+    }
+}
+
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, StoreError>> + Send + 'a>>;
+
+/// One answer (or timeout) for the `responses` table
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ResponseRecord {
+    pub session_id: String,
+    /// Position within the session, so a replayed write doesn't add a second row
+    #[serde(default)]
+    pub seq: i32,
+    /// Who answered, when more than one person plays a session
+    #[serde(default)]
+    pub player: Option<String>,
+    pub question_id: Option<i32>,
+    pub question_text: String,
+    pub choice: Option<Choice>,
+    #[serde(default)]
+    pub outcome: Outcome,
+    /// The timer ran out and the timeout policy chose for the player
+    #[serde(default)]
+    pub auto_picked: bool,
+    pub reaction_ms: Option<i32>,
+}
+
+/// How a response came about, stored in `responses.outcome`
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    #[default]
+    Answered,
+    /// The timer ran out. There is still a choice if the timeout policy picked one.
+    TimedOut,
+    /// Passed on with the SKIP action
+    Skipped,
+}
+
+impl Outcome {
+    fn code(self) -> &'static str {
+        match self {
+            Outcome::Answered => "answered",
+            Outcome::TimedOut => "timed_out",
+            Outcome::Skipped => "skipped",
+        }
+    }
+}
+
+/// Everything written when a session reaches the results screen
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FinishedSession {
+    pub session_id: String,
+    /// Game mode code, e.g. `solo` or `couples`
+    #[serde(default = "solo_mode")]
+    pub mode: String,
+    pub score_left: i32,
+    pub score_right: i32,
+    /// How many of the answers in the scores were picked on timeout
+    #[serde(default)]
+    pub auto_picks: i32,
+    pub result_type: String,
+    /// Couples: percentage of questions the partners agreed on; guess: predictions that were right
+    #[serde(default)]
+    pub compatibility: Option<i32>,
+    pub traits: Vec<TraitScore>,
+}
+
+fn solo_mode() -> String {
+    "solo".into()
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TraitScore {
+    pub name: String,
+    pub value: String,
+    pub score: i32,
+}
+
+/// How everyone else has played, over sessions with at least 3 answers
+#[derive(Clone, Default)]
+pub struct GlobalStats {
+    pub players: i64,
+    pub avg_left_pct: f64,
+}
+
+#[derive(Clone)]
+pub struct LeaderboardEntry {
+    pub session_id: String,
+    pub answers: i64,
+}
+
+/// A single write, as applied to a store or kept on disk until it can be
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScoreWrite {
+    SessionStart { session_id: String },
+    Response(ResponseRecord),
+    Finished(FinishedSession),
+}
+
+impl ScoreWrite {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScoreWrite::SessionStart { .. } => "session start",
+            ScoreWrite::Response(_) => "response",
+            ScoreWrite::Finished(_) => "game score",
+        }
+    }
+}
+
+/// Sessions shorter than this are left out of the global stats
+const MIN_ANSWERS: i32 = 3;
+
+/// `result_type` of a session restarted before its results. These are kept but left out of the
+/// global stats and the leaderboard.
+pub const ABANDONED: &str = "abandoned";
+
+pub trait ScoreStore: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    fn start_session(&self, session_id: String) -> StoreFuture<'_, ()>;
+
+    fn record_response(&self, record: ResponseRecord) -> StoreFuture<'_, ()>;
+
+    fn finish_session(&self, session: FinishedSession) -> StoreFuture<'_, ()>;
+
+    /// Applies `write`. Every write is idempotent, so replaying one is harmless.
+    fn apply(&self, write: ScoreWrite) -> StoreFuture<'_, ()> {
+        match write {
+            ScoreWrite::SessionStart { session_id } => self.start_session(session_id),
+            ScoreWrite::Response(record) => self.record_response(record),
+            ScoreWrite::Finished(session) => self.finish_session(session),
+        }
+    }
+
+    fn stats(&self) -> StoreFuture<'_, GlobalStats>;
+
+    /// Longest sessions first
+    fn leaderboard(&self, limit: usize) -> StoreFuture<'_, Vec<LeaderboardEntry>>;
+
+    /// Cheap round trip used to notice a dropped connection
+    fn ping(&self) -> StoreFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Deck stored alongside the scores. Only MySQL has a `questions` table.
+    fn questions(&self) -> StoreFuture<'_, Vec<Q>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+}
+
+/// Connects to the backend named by `url` and brings its schema up to date
+pub async fn open(url: &str) -> Result<Arc<dyn ScoreStore>, StoreError> {
+    let Some((scheme, rest)) = url.split_once(':') else {
+        return Err("DATABASE_URL has no scheme".into());
+    };
+    match scheme {
+        "mysql" => Ok(Arc::new(MySqlStore::connect(url).await?)),
+        "sqlite" => Ok(Arc::new(SqliteStore::connect(url).await?)),
+        "jsonl" => Ok(Arc::new(JsonLinesStore::new(rest.trim_start_matches("//")))),
+        "memory" => Ok(Arc::new(MemoryStore::default())),
+        other => Err(format!("unsupported DATABASE_URL scheme '{}'", other).into()),
+    }
+}
+
+fn choice_code(choice: Option<Choice>) -> Option<&'static str> {
+    choice.map(|c| match c {
+        Choice::Left => "A",
+        Choice::Right => "B",
+    })
+}
+
+/// A response queued before outcomes were recorded defaults to answered even without a choice,
+/// which only a timeout could leave
+fn outcome_code(record: &ResponseRecord) -> &'static str {
+    match record.outcome {
+        Outcome::Answered if record.choice.is_none() => Outcome::TimedOut.code(),
+        outcome => outcome.code(),
+    }
+}
+
+fn summarize(finished: &[FinishedSession]) -> GlobalStats {
+    let counted: Vec<f64> = finished
+        .iter()
+        .filter(|s| s.score_left + s.score_right >= MIN_ANSWERS && s.result_type != ABANDONED)
+        .map(|s| s.score_left as f64 * 100.0 / (s.score_left + s.score_right) as f64)
+        .collect();
+    GlobalStats {
+        players: counted.len() as i64,
+        avg_left_pct: if counted.is_empty() {
+            50.0
+        } else {
+            counted.iter().sum::<f64>() / counted.len() as f64
+        },
+    }
+}
+
+fn top_runs(finished: &[FinishedSession], limit: usize) -> Vec<LeaderboardEntry> {
+    let mut runs: Vec<LeaderboardEntry> = finished
+        .iter()
+        .filter(|s| s.result_type != ABANDONED)
+        .map(|s| LeaderboardEntry {
+            session_id: s.session_id.clone(),
+            answers: (s.score_left + s.score_right) as i64,
+        })
+        .collect();
+    runs.sort_by_key(|r| std::cmp::Reverse(r.answers));
+    runs.truncate(limit);
+    runs
+}
+
+// ---------------------------------------------------------------------------
+// MySQL
+
+/// Versioned schema from `migrations/mysql/`, embedded at build time
+static MYSQL_MIGRATOR: Migrator = sqlx::migrate!("migrations/mysql");
+
+/// The first migrations are the original hand-run setup scripts, kept as they were
+const SETUP_SCRIPTS: i64 = 3;
+
+/// `MYSQL_MIGRATOR` made to run against the database in `DATABASE_URL`. The setup scripts
+/// create and switch to a database of their own, so those statements are left out.
+/// `ADD COLUMN IF NOT EXISTS` is MariaDB only, and the column can only already exist in a
+/// database that `adopt_setup_scripts` takes care of.
+#[derive(Debug)]
+struct MySqlMigrations;
+
+impl MigrationSource<'static> for MySqlMigrations {
+    fn resolve(self) -> Pin<Box<dyn Future<Output = Result<Vec<Migration>, BoxDynError>> + Send>> {
+        let migrations = MYSQL_MIGRATOR
+            .iter()
+            .map(|m| {
+                let sql = if m.version <= SETUP_SCRIPTS { for_database_url(&m.sql) } else { m.sql.to_string() };
+                Migration::new(m.version, m.description.clone(), m.migration_type, sql.into(), m.no_tx)
+            })
+            .collect();
+        Box::pin(async move { Ok(migrations) })
+    }
+}
+
+fn for_database_url(sql: &str) -> String {
+    sql.lines()
+        .filter(|line| {
+            let line = line.trim_start().to_uppercase();
+            !line.starts_with("CREATE DATABASE") && !line.starts_with("USE ")
+        })
+        .map(|line| format!("{}\n", line.replace("ADD COLUMN IF NOT EXISTS", "ADD COLUMN")))
+        .collect()
+}
+
+/// A database set up by hand from the setup scripts has their tables but no record of
+/// them in `_sqlx_migrations`. Running them again would add a second copy of the deck, so
+/// the ones that were run are recorded as applied instead.
+async fn adopt_setup_scripts(pool: &MySqlPool, migrator: &Migrator) -> Result<(), StoreError> {
+    let exists = |table: &'static str| {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = ?",
+        )
+        .bind(table)
+        .fetch_one(pool)
+    };
+    if exists("_sqlx_migrations").await? > 0 || exists("questions").await? == 0 {
+        return Ok(());
+    }
+
+    // 001 made the tables, 002 added `category`, 003 added more questions after it
+    let has_category: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM information_schema.columns \
+         WHERE table_schema = DATABASE() AND table_name = 'questions' AND column_name = 'category'",
+    )
+    .fetch_one(pool)
+    .await?;
+    let has_more: i64 = if has_category > 0 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM questions WHERE question_text = 'First date energy...'")
+            .fetch_one(pool)
+            .await?
+    } else {
+        0
+    };
+    let run = 1 + i64::from(has_category > 0) + i64::from(has_more > 0);
+
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    for m in migrator.iter().filter(|m| m.version <= run) {
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+             VALUES (?, ?, TRUE, ?, 0)",
+        )
+        .bind(m.version)
+        .bind(m.description.as_ref())
+        .bind(m.checksum.as_ref())
+        .execute(&mut *conn)
+        .await?;
+    }
+    info!("Adopted a hand-made database, setup scripts up to {} were already run", run);
+    Ok(())
+}
+
+pub struct MySqlStore {
+    pool: MySqlPool,
+}
+
+impl MySqlStore {
+    async fn connect(url: &str) -> Result<Self, StoreError> {
+        // A short acquire timeout makes writes fail fast, so they get queued, while the server is away
+        let pool = MySqlPoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(Duration::from_secs(5))
+            .connect(url)
+            .await?;
+
+        let migrator = Migrator::new(MySqlMigrations).await?;
+        if let Err(e) = adopt_setup_scripts(&pool, &migrator).await {
+            warn!("Couldn't check for a hand-made schema: {}", e);
+        }
+        if let Err(e) = migrator.run(&pool).await {
+            warn!("Failed to apply migrations: {}", e);
+        }
+
+        // Writers only ever see a pool whose schema matches this build
+        let latest = migrator.iter().map(|m| m.version).max().unwrap_or(0);
+        let applied: i64 = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT MAX(version) FROM _sqlx_migrations WHERE success = TRUE",
+        )
+        .fetch_one(&pool)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(0);
+        if applied < latest {
+            return Err(format!(
+                "database schema is at version {} but this build needs {}",
+                applied, latest
+            )
+            .into());
+        }
+        info!("MySQL connected, schema at version {}", applied);
+
+        Ok(Self { pool })
+    }
+}
+
+impl ScoreStore for MySqlStore {
+    fn name(&self) -> &'static str {
+        "MySQL"
+    }
+
+    fn ping(&self) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            sqlx::query("SELECT 1").execute(&self.pool).await?;
+            Ok(())
+        })
+    }
+
+    fn start_session(&self, session_id: String) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            sqlx::query("INSERT INTO player_sessions (id) VALUES (?) ON DUPLICATE KEY UPDATE id = id")
+                .bind(&session_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn record_response(&self, record: ResponseRecord) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            // The session row may not have landed yet if the connection came up mid-session
+            sqlx::query("INSERT INTO player_sessions (id) VALUES (?) ON DUPLICATE KEY UPDATE id = id")
+                .bind(&record.session_id)
+                .execute(&self.pool)
+                .await?;
+            sqlx::query(
+                "INSERT INTO responses (session_id, player, seq, question_id, question_text, choice, outcome, auto_picked, reaction_ms) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) \
+                 ON DUPLICATE KEY UPDATE id = id",
+            )
+            .bind(&record.session_id)
+            .bind(&record.player)
+            .bind(record.seq)
+            .bind(record.question_id)
+            .bind(&record.question_text)
+            .bind(choice_code(record.choice))
+            .bind(outcome_code(&record))
+            .bind(record.auto_picked)
+            .bind(record.reaction_ms)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn finish_session(&self, session: FinishedSession) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            // personality_scores rows hang off a player session
+            sqlx::query("INSERT INTO player_sessions (id) VALUES (?) ON DUPLICATE KEY UPDATE id = id")
+                .bind(&session.session_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("UPDATE player_sessions SET completed_at = CURRENT_TIMESTAMP WHERE id = ?")
+                .bind(&session.session_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO game_scores (session_id, mode, score_left, score_right, auto_picks, result_type, compatibility) VALUES (?, ?, ?, ?, ?, ?, ?) \
+                 ON DUPLICATE KEY UPDATE id = id",
+            )
+            .bind(&session.session_id)
+            .bind(&session.mode)
+            .bind(session.score_left)
+            .bind(session.score_right)
+            .bind(session.auto_picks)
+            .bind(&session.result_type)
+            .bind(session.compatibility)
+            .execute(&mut *tx)
+            .await?;
+            for t in &session.traits {
+                sqlx::query(
+                    "INSERT INTO personality_scores (session_id, trait_name, trait_value, score) VALUES (?, ?, ?, ?) \
+                     ON DUPLICATE KEY UPDATE trait_value = VALUES(trait_value), score = VALUES(score)",
+                )
+                .bind(&session.session_id)
+                .bind(&t.name)
+                .bind(&t.value)
+                .bind(t.score)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            Ok(())
+        })
+    }
+
+    fn stats(&self) -> StoreFuture<'_, GlobalStats> {
+        Box::pin(async move {
+            // AVG over integers comes back as DECIMAL, which won't decode into f64
+            let (players, avg): (i64, Option<f64>) = sqlx::query_as(
+                "SELECT COUNT(*), CAST(AVG(score_left * 100.0 / (score_left + score_right)) AS DOUBLE) \
+                 FROM game_scores WHERE score_left + score_right >= ? AND result_type <> ?",
+            )
+            .bind(MIN_ANSWERS)
+            .bind(ABANDONED)
+            .fetch_one(&self.pool)
+            .await?;
+            Ok(GlobalStats {
+                players,
+                avg_left_pct: avg.unwrap_or(50.0),
+            })
+        })
+    }
+
+    fn leaderboard(&self, limit: usize) -> StoreFuture<'_, Vec<LeaderboardEntry>> {
+        Box::pin(async move {
+            let rows: Vec<(String, i64)> = sqlx::query_as(
+                "SELECT session_id, CAST(score_left + score_right AS SIGNED) AS answers \
+                 FROM game_scores WHERE result_type <> ? ORDER BY answers DESC LIMIT ?",
+            )
+            .bind(ABANDONED)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(session_id, answers)| LeaderboardEntry { session_id, answers })
+                .collect())
+        })
+    }
+
+    fn questions(&self) -> StoreFuture<'_, Vec<Q>> {
+        Box::pin(async move {
+            let rows = sqlx::query(
+                "SELECT id, question_text, option_a_text, option_a_emoji, option_b_text, option_b_emoji, trait_name, category FROM questions",
+            )
+            .fetch_all(&self.pool)
+            .await?;
+
+            let deck = rows
+                .iter()
+                .map(|row| {
+                    Ok(Q {
+                        id: row.try_get("id")?,
+                        title: row.try_get("question_text")?,
+                        left: row.try_get::<String, _>("option_a_text")?.to_uppercase(),
+                        left_em: row.try_get("option_a_emoji")?,
+                        right: row.try_get::<String, _>("option_b_text")?.to_uppercase(),
+                        right_em: row.try_get("option_b_emoji")?,
+                        trait_name: row.try_get("trait_name")?,
+                        category: row
+                            .try_get::<Option<String>, _>("category")?
+                            .unwrap_or_else(|| "general".into()),
+                        rating: None,
+                    })
+                })
+                .collect::<Result<Vec<Q>, sqlx::Error>>()?;
+            Ok(deck)
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SQLite
+
+/// SQLite flavour of the schema, from `migrations/sqlite/`
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    async fn connect(url: &str) -> Result<Self, StoreError> {
+        let options = SqliteConnectOptions::from_str(url)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await?;
+        SQLITE_MIGRATOR.run(&pool).await?;
+        info!("SQLite database opened at {}", url);
+        Ok(Self { pool })
+    }
+}
+
+impl ScoreStore for SqliteStore {
+    fn name(&self) -> &'static str {
+        "SQLite"
+    }
+
+    fn ping(&self) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            sqlx::query("SELECT 1").execute(&self.pool).await?;
+            Ok(())
+        })
+    }
+
+    fn start_session(&self, session_id: String) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            sqlx::query("INSERT INTO player_sessions (id) VALUES (?) ON CONFLICT DO NOTHING")
+                .bind(&session_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn record_response(&self, record: ResponseRecord) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            sqlx::query("INSERT INTO player_sessions (id) VALUES (?) ON CONFLICT DO NOTHING")
+                .bind(&record.session_id)
+                .execute(&self.pool)
+                .await?;
+            sqlx::query(
+                "INSERT INTO responses (session_id, player, seq, question_id, question_text, choice, outcome, auto_picked, reaction_ms) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) \
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(&record.session_id)
+            .bind(&record.player)
+            .bind(record.seq)
+            .bind(record.question_id)
+            .bind(&record.question_text)
+            .bind(choice_code(record.choice))
+            .bind(outcome_code(&record))
+            .bind(record.auto_picked)
+            .bind(record.reaction_ms)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn finish_session(&self, session: FinishedSession) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("INSERT INTO player_sessions (id) VALUES (?) ON CONFLICT DO NOTHING")
+                .bind(&session.session_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("UPDATE player_sessions SET completed_at = CURRENT_TIMESTAMP WHERE id = ?")
+                .bind(&session.session_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO game_scores (session_id, mode, score_left, score_right, auto_picks, result_type, compatibility) VALUES (?, ?, ?, ?, ?, ?, ?) \
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(&session.session_id)
+            .bind(&session.mode)
+            .bind(session.score_left)
+            .bind(session.score_right)
+            .bind(session.auto_picks)
+            .bind(&session.result_type)
+            .bind(session.compatibility)
+            .execute(&mut *tx)
+            .await?;
+            for t in &session.traits {
+                sqlx::query(
+                    "INSERT INTO personality_scores (session_id, trait_name, trait_value, score) VALUES (?, ?, ?, ?) \
+                     ON CONFLICT (session_id, trait_name) DO UPDATE SET \
+                     trait_value = excluded.trait_value, score = excluded.score, updated_at = CURRENT_TIMESTAMP",
+                )
+                .bind(&session.session_id)
+                .bind(&t.name)
+                .bind(&t.value)
+                .bind(t.score)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            Ok(())
+        })
+    }
+
+    fn stats(&self) -> StoreFuture<'_, GlobalStats> {
+        Box::pin(async move {
+            let (players, avg): (i64, Option<f64>) = sqlx::query_as(
+                "SELECT COUNT(*), AVG(score_left * 100.0 / (score_left + score_right)) \
+                 FROM game_scores WHERE score_left + score_right >= ? AND result_type <> ?",
+            )
+            .bind(MIN_ANSWERS)
+            .bind(ABANDONED)
+            .fetch_one(&self.pool)
+            .await?;
+            Ok(GlobalStats {
+                players,
+                avg_left_pct: avg.unwrap_or(50.0),
+            })
+        })
+    }
+
+    fn leaderboard(&self, limit: usize) -> StoreFuture<'_, Vec<LeaderboardEntry>> {
+        Box::pin(async move {
+            let rows: Vec<(String, i64)> = sqlx::query_as(
+                "SELECT session_id, score_left + score_right AS answers \
+                 FROM game_scores WHERE result_type <> ? ORDER BY answers DESC LIMIT ?",
+            )
+            .bind(ABANDONED)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(session_id, answers)| LeaderboardEntry { session_id, answers })
+                .collect())
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JSON lines
+
+/// One line of the journal file, and of the write queue
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    /// Unix seconds
+    at: u64,
+    #[serde(flatten)]
+    write: ScoreWrite,
+    /// Why the store refused it, for lines in the dead-letter file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl JournalEntry {
+    fn line(write: ScoreWrite) -> Result<String, StoreError> {
+        Self::line_with(write, None)
+    }
+
+    fn line_with(write: ScoreWrite, error: Option<String>) -> Result<String, StoreError> {
+        let entry = JournalEntry {
+            at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            write,
+            error,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        Ok(line)
+    }
+}
+
+/// Appends `text` to `path`, creating the file if needed
+async fn append_to(path: &Path, text: &str) -> Result<(), StoreError> {
+    let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+    file.write_all(text.as_bytes()).await?;
+    // tokio finishes file writes in the background; without this the last line can be lost
+    file.flush().await?;
+    Ok(())
+}
+
+/// Contents of `path`, or an empty string if it doesn't exist yet
+async fn read_or_empty(path: &Path) -> Result<String, StoreError> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(text) => Ok(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Appends every write to a local file, one JSON object per line.
+/// Stats are computed by reading the file back, which is fine at booth scale.
+pub struct JsonLinesStore {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl JsonLinesStore {
+    fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        info!("Saving scores to {}", path.display());
+        Self {
+            path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    async fn append(&self, write: ScoreWrite) -> Result<(), StoreError> {
+        let line = JournalEntry::line(write)?;
+        let _guard = self.write_lock.lock().await;
+        append_to(&self.path, &line).await
+    }
+
+    /// Finished sessions in the file, the last write winning for each session
+    async fn finished(&self) -> Result<Vec<FinishedSession>, StoreError> {
+        let text = read_or_empty(&self.path).await?;
+        let mut finished: Vec<FinishedSession> = Vec::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str::<JournalEntry>(line) {
+                Ok(JournalEntry {
+                    write: ScoreWrite::Finished(session),
+                    ..
+                }) => {
+                    finished.retain(|s| s.session_id != session.session_id);
+                    finished.push(session);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Skipping unreadable line in {}: {}", self.path.display(), e),
+            }
+        }
+        Ok(finished)
+    }
+}
+
+impl ScoreStore for JsonLinesStore {
+    fn name(&self) -> &'static str {
+        "JSON lines"
+    }
+
+    fn start_session(&self, session_id: String) -> StoreFuture<'_, ()> {
+        Box::pin(self.append(ScoreWrite::SessionStart { session_id }))
+    }
+
+    fn record_response(&self, record: ResponseRecord) -> StoreFuture<'_, ()> {
+        Box::pin(self.append(ScoreWrite::Response(record)))
+    }
+
+    fn finish_session(&self, session: FinishedSession) -> StoreFuture<'_, ()> {
+        Box::pin(self.append(ScoreWrite::Finished(session)))
+    }
+
+    fn stats(&self) -> StoreFuture<'_, GlobalStats> {
+        Box::pin(async move { Ok(summarize(&self.finished().await?)) })
+    }
+
+    fn leaderboard(&self, limit: usize) -> StoreFuture<'_, Vec<LeaderboardEntry>> {
+        Box::pin(async move { Ok(top_runs(&self.finished().await?, limit)) })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// In memory
+
+/// Keeps everything for the lifetime of the process. Handy for tests and demos.
+#[derive(Default)]
+pub struct MemoryStore {
+    inner: Mutex<MemoryInner>,
+}
+
+#[derive(Default)]
+struct MemoryInner {
+    sessions: HashSet<String>,
+    responses: Vec<ResponseRecord>,
+    finished: Vec<FinishedSession>,
+}
+
+impl ScoreStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn start_session(&self, session_id: String) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            self.inner.lock().await.sessions.insert(session_id);
+            Ok(())
+        })
+    }
+
+    fn record_response(&self, record: ResponseRecord) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            let mut inner = self.inner.lock().await;
+            inner.sessions.insert(record.session_id.clone());
+            inner
+                .responses
+                .retain(|r| r.session_id != record.session_id || r.seq != record.seq);
+            inner.responses.push(record);
+            Ok(())
+        })
+    }
+
+    fn finish_session(&self, session: FinishedSession) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            let mut inner = self.inner.lock().await;
+            inner.sessions.insert(session.session_id.clone());
+            inner.finished.retain(|s| s.session_id != session.session_id);
+            inner.finished.push(session);
+            Ok(())
+        })
+    }
+
+    fn stats(&self) -> StoreFuture<'_, GlobalStats> {
+        Box::pin(async move { Ok(summarize(&self.inner.lock().await.finished)) })
+    }
+
+    fn leaderboard(&self, limit: usize) -> StoreFuture<'_, Vec<LeaderboardEntry>> {
+        Box::pin(async move { Ok(top_runs(&self.inner.lock().await.finished, limit)) })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Write-ahead queue
+
+/// Writes that couldn't reach the store, kept on disk in the journal format until they can.
+/// Replay applies them in order and stops at the first transient failure, keeping the rest.
+/// Writes the store refuses outright (bad data, a broken constraint) would fail the same way
+/// every time, so they're moved to a dead-letter file next to the queue instead.
+pub struct WriteQueue {
+    path: PathBuf,
+    rejected: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl WriteQueue {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            rejected: path.with_extension("rejected.jsonl"),
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Where refused writes end up
+    pub fn rejected_path(&self) -> &Path {
+        &self.rejected
+    }
+
+    /// Sets aside a write the store refused, with the reason
+    pub async fn reject(&self, write: ScoreWrite, error: &StoreError) -> Result<(), StoreError> {
+        let line = JournalEntry::line_with(write, Some(error.to_string()))?;
+        let _guard = self.lock.lock().await;
+        append_to(&self.rejected, &line).await
+    }
+
+    pub async fn push(&self, write: ScoreWrite) -> Result<(), StoreError> {
+        let line = JournalEntry::line(write)?;
+        let _guard = self.lock.lock().await;
+        append_to(&self.path, &line).await
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        tokio::fs::metadata(&self.path).await.map(|m| m.len() == 0).unwrap_or(true)
+    }
+
+    /// Applies queued writes to `store`, returning how many landed. Refused and unreadable
+    /// writes are moved to the dead-letter file and don't count.
+    pub async fn replay(&self, store: &dyn ScoreStore) -> Result<usize, StoreError> {
+        let _guard = self.lock.lock().await;
+        let text = read_or_empty(&self.path).await?;
+        let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+
+        let mut done = 0;
+        let mut landed = 0;
+        for line in &lines {
+            let write = match serde_json::from_str::<JournalEntry>(line) {
+                Ok(entry) => entry.write,
+                Err(e) => {
+                    warn!("Unreadable queued write, moved to {}: {}", self.rejected.display(), e);
+                    append_to(&self.rejected, &format!("{}\n", line)).await?;
+                    done += 1;
+                    continue;
+                }
+            };
+            match store.apply(write.clone()).await {
+                Ok(()) => landed += 1,
+                Err(e) if e.is_transient() => {
+                    warn!("Replay stopped, {} writes still queued: {}", lines.len() - done, e);
+                    break;
+                }
+                Err(e) => {
+                    warn!("{} refused a queued write, moved to {}: {}", store.name(), self.rejected.display(), e);
+                    append_to(&self.rejected, &JournalEntry::line_with(write, Some(e.to_string()))?).await?;
+                }
+            }
+            done += 1;
+        }
+
+        if done == lines.len() {
+            if !lines.is_empty() {
+                tokio::fs::remove_file(&self.path).await?;
+            }
+        } else {
+            // Rewrite through a temporary file so a crash can't lose the rest
+            let tmp = self.path.with_extension("tmp");
+            let rest: String = lines[done..].iter().map(|l| format!("{}\n", l)).collect();
+            tokio::fs::write(&tmp, rest).await?;
+            tokio::fs::rename(&tmp, &self.path).await?;
+        }
+        Ok(landed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(session_id: &str, seq: i32, choice: Choice) -> ResponseRecord {
+        ResponseRecord {
+            session_id: session_id.into(),
+            seq,
+            player: None,
+            question_id: None,
+            question_text: format!("question {}", seq),
+            choice: Some(choice),
+            outcome: Outcome::Answered,
+            auto_picked: false,
+            reaction_ms: Some(800),
+        }
+    }
+
+    fn finished(session_id: &str, score_left: i32, score_right: i32) -> FinishedSession {
+        FinishedSession {
+            session_id: session_id.into(),
+            mode: "solo".into(),
+            score_left,
+            score_right,
+            auto_picks: 0,
+            result_type: "perfectly_balanced".into(),
+            compatibility: None,
+            traits: Vec::new(),
+        }
+    }
+
+    /// A queue file in the temp directory, with no leftovers from an earlier run
+    fn queue(name: &str) -> WriteQueue {
+        let path = std::env::temp_dir().join(format!("two-people-{}-{}.jsonl", name, std::process::id()));
+        let queue = WriteQueue::new(path);
+        let _ = std::fs::remove_file(&queue.path);
+        let _ = std::fs::remove_file(&queue.rejected);
+        queue
+    }
+
+    /// Refuses responses as bad data, and finished sessions as if the connection dropped
+    #[derive(Default)]
+    struct Picky {
+        memory: MemoryStore,
+    }
+
+    impl ScoreStore for Picky {
+        fn name(&self) -> &'static str {
+            "picky"
+        }
+
+        fn start_session(&self, session_id: String) -> StoreFuture<'_, ()> {
+            self.memory.start_session(session_id)
+        }
+
+        fn record_response(&self, _: ResponseRecord) -> StoreFuture<'_, ()> {
+            Box::pin(async { Err(serde_json::from_str::<i32>("not a number").unwrap_err().into()) })
+        }
+
+        fn finish_session(&self, _: FinishedSession) -> StoreFuture<'_, ()> {
+            Box::pin(async { Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset).into()) })
+        }
+
+        fn stats(&self) -> StoreFuture<'_, GlobalStats> {
+            self.memory.stats()
+        }
+
+        fn leaderboard(&self, limit: usize) -> StoreFuture<'_, Vec<LeaderboardEntry>> {
+            self.memory.leaderboard(limit)
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_store_round_trip() {
+        let store = MemoryStore::default();
+        store.start_session("a".into()).await.unwrap();
+        for seq in 1..=4 {
+            store.record_response(response("a", seq, Choice::Left)).await.unwrap();
+        }
+        store.finish_session(finished("a", 3, 1)).await.unwrap();
+        store.finish_session(finished("b", 1, 1)).await.unwrap();
+
+        let inner = store.inner.lock().await;
+        assert!(inner.sessions.contains("a") && inner.sessions.contains("b"));
+        assert_eq!(inner.responses.len(), 4);
+        drop(inner);
+
+        // "b" is too short to count
+        let stats = store.stats().await.unwrap();
+        assert_eq!(stats.players, 1);
+        assert_eq!(stats.avg_left_pct, 75.0);
+        let board = store.leaderboard(5).await.unwrap();
+        assert_eq!(board.iter().map(|e| e.session_id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+    }
+
+    #[test]
+    fn summarize_averages_sessions_long_enough() {
+        assert_eq!(summarize(&[]).players, 0);
+        assert_eq!(summarize(&[]).avg_left_pct, 50.0);
+
+        let stats = summarize(&[finished("a", 3, 1), finished("b", 0, 4), finished("c", 2, 0)]);
+        assert_eq!(stats.players, 2);
+        assert_eq!(stats.avg_left_pct, 37.5);
+    }
+
+    #[test]
+    fn abandoned_sessions_are_left_out() {
+        let abandoned = FinishedSession {
+            result_type: ABANDONED.into(),
+            ..finished("b", 9, 0)
+        };
+        let sessions = [finished("a", 2, 2), abandoned];
+        assert_eq!(summarize(&sessions).players, 1);
+        let runs = top_runs(&sessions, 3);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].session_id, "a");
+    }
+
+    #[test]
+    fn top_runs_longest_first() {
+        let runs = top_runs(&[finished("a", 1, 1), finished("b", 5, 4), finished("c", 2, 3)], 2);
+        let order: Vec<(&str, i64)> = runs.iter().map(|r| (r.session_id.as_str(), r.answers)).collect();
+        assert_eq!(order, [("b", 9), ("c", 5)]);
+    }
+
+    #[test]
+    fn setup_scripts_run_in_the_database_url_database() {
+        let init = for_database_url(include_str!("../migrations/mysql/001_init.sql"));
+        let questions = for_database_url(include_str!("../migrations/mysql/002_questions.sql"));
+        for sql in [&init, &questions] {
+            assert!(!sql.lines().any(|l| l.starts_with("USE ") || l.starts_with("CREATE DATABASE")));
+        }
+        assert!(init.contains("CREATE TABLE IF NOT EXISTS questions"));
+        assert!(questions.contains("ALTER TABLE questions ADD COLUMN category VARCHAR(50) DEFAULT 'general';"));
+    }
+
+    #[tokio::test]
+    async fn later_migrations_keep_their_checksums() {
+        let adapted = Migrator::new(MySqlMigrations).await.unwrap();
+        assert_eq!(adapted.iter().count(), MYSQL_MIGRATOR.iter().count());
+        for (ours, embedded) in adapted.iter().zip(MYSQL_MIGRATOR.iter()) {
+            assert_eq!(ours.version, embedded.version);
+            if ours.version > SETUP_SCRIPTS {
+                assert_eq!(ours.checksum, embedded.checksum);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_applies_queue_and_removes_file() {
+        let queue = queue("replay");
+        queue.push(ScoreWrite::SessionStart { session_id: "a".into() }).await.unwrap();
+        queue.push(ScoreWrite::Response(response("a", 1, Choice::Right))).await.unwrap();
+        queue.push(ScoreWrite::Finished(finished("a", 0, 1))).await.unwrap();
+        assert!(!queue.is_empty().await);
+
+        let store = MemoryStore::default();
+        assert_eq!(queue.replay(&store).await.unwrap(), 3);
+        assert!(!queue.path.exists());
+        assert!(queue.is_empty().await);
+        assert_eq!(store.inner.lock().await.finished.len(), 1);
+
+        // Nothing left to do the second time round
+        assert_eq!(queue.replay(&store).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn replayed_response_lands_once() {
+        let memory = MemoryStore::default();
+        let sqlite = SqliteStore::connect("sqlite::memory:").await.unwrap();
+        for _ in 0..2 {
+            memory.apply(ScoreWrite::Response(response("a", 1, Choice::Left))).await.unwrap();
+            sqlite.apply(ScoreWrite::Response(response("a", 1, Choice::Left))).await.unwrap();
+            sqlite.apply(ScoreWrite::Finished(finished("a", 1, 0))).await.unwrap();
+        }
+        assert_eq!(memory.inner.lock().await.responses.len(), 1);
+        let count = |table: &str| format!("SELECT COUNT(*) FROM {}", table);
+        let responses: i64 = sqlx::query_scalar(&count("responses")).fetch_one(&sqlite.pool).await.unwrap();
+        let scores: i64 = sqlx::query_scalar(&count("game_scores")).fetch_one(&sqlite.pool).await.unwrap();
+        assert_eq!((responses, scores), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn outcomes_tell_skips_from_timeouts() {
+        let sqlite = SqliteStore::connect("sqlite::memory:").await.unwrap();
+        let skipped = ResponseRecord {
+            choice: None,
+            outcome: Outcome::Skipped,
+            ..response("a", 1, Choice::Left)
+        };
+        // Queued before outcomes were recorded
+        let timed_out: ResponseRecord = serde_json::from_str(
+            r#"{"session_id":"a","seq":2,"question_id":null,"question_text":"q","choice":null,"reaction_ms":null}"#,
+        )
+        .unwrap();
+        for record in [skipped, timed_out, response("a", 3, Choice::Right)] {
+            sqlite.record_response(record).await.unwrap();
+        }
+        let outcomes: Vec<String> = sqlx::query_scalar("SELECT outcome FROM responses ORDER BY seq")
+            .fetch_all(&sqlite.pool)
+            .await
+            .unwrap();
+        assert_eq!(outcomes, ["skipped", "timed_out", "answered"]);
+    }
+
+    #[tokio::test]
+    async fn constraint_errors_are_not_retried() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE t (id INTEGER PRIMARY KEY, x INTEGER NOT NULL)").execute(&pool).await.unwrap();
+        let null = sqlx::query("INSERT INTO t (x) VALUES (NULL)").execute(&pool).await.unwrap_err();
+        assert_eq!(StoreError::from(null).kind, ErrorKind::Data);
+
+        assert!(StoreError::from(std::io::Error::from(std::io::ErrorKind::TimedOut)).is_transient());
+    }
+
+    #[tokio::test]
+    async fn replay_sets_aside_refused_writes_and_keeps_transient_ones() {
+        let queue = queue("dead-letter");
+        queue.push(ScoreWrite::Response(response("a", 1, Choice::Left))).await.unwrap();
+        queue.push(ScoreWrite::SessionStart { session_id: "a".into() }).await.unwrap();
+        queue.push(ScoreWrite::Finished(finished("a", 1, 0))).await.unwrap();
+        queue.push(ScoreWrite::SessionStart { session_id: "b".into() }).await.unwrap();
+
+        // The refused response doesn't hold up the session start behind it, but the dropped
+        // connection stops the replay with the rest still queued
+        let store = Picky::default();
+        assert_eq!(queue.replay(&store).await.unwrap(), 1);
+        let rejected = std::fs::read_to_string(&queue.rejected).unwrap();
+        assert_eq!(rejected.lines().count(), 1);
+        assert!(rejected.contains("\"kind\":\"response\"") && rejected.contains("\"error\""));
+        let left = std::fs::read_to_string(&queue.path).unwrap();
+        assert_eq!(left.lines().count(), 2);
+        assert!(left.contains("\"kind\":\"finished\""));
+
+        // Once the store takes them, the queue empties and the dead letter stays put
+        assert_eq!(queue.replay(&MemoryStore::default()).await.unwrap(), 2);
+        assert!(queue.is_empty().await);
+        assert_eq!(std::fs::read_to_string(&queue.rejected).unwrap(), rejected);
+        std::fs::remove_file(&queue.rejected).unwrap();
+    }
+}